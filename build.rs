@@ -0,0 +1,36 @@
+//! Validates `SYS_LOCALE_DEFAULT` and exports it as `SYS_LOCALE_CONST_LOCALE` for `const_locale`.
+
+use std::env;
+
+/// The variable holding the locale returned by `const_locale`.
+const DEFAULT_VAR: &str = "SYS_LOCALE_DEFAULT";
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed={}", DEFAULT_VAR);
+
+    let tag = match env::var(DEFAULT_VAR) {
+        Ok(tag) => tag,
+        Err(_) => return,
+    };
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return;
+    }
+
+    // The crate itself can't be used here, so this only checks the general shape of a BCP 47
+    // tag. That's enough to catch a POSIX name like `en_US.UTF-8` being used by mistake.
+    let well_formed = tag.split('-').enumerate().all(|(i, subtag)| {
+        (1..=8).contains(&subtag.len())
+            && subtag.bytes().all(|b| b.is_ascii_alphanumeric())
+            && (i > 0 || subtag.bytes().all(|b| b.is_ascii_alphabetic()))
+    });
+    if !well_formed {
+        panic!(
+            "{} must be a BCP 47 language tag like `en-US`, but is {:?}",
+            DEFAULT_VAR, tag
+        );
+    }
+
+    println!("cargo:rustc-env=SYS_LOCALE_CONST_LOCALE={}", tag);
+}
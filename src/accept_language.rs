@@ -0,0 +1,136 @@
+use alloc::{string::String, vec::Vec};
+
+/// Parses an HTTP `Accept-Language` header into language tags, most preferred first.
+///
+/// Entries are ordered by their quality value (`q`), keeping the order of the header for
+/// entries with equal quality. An entry without a `q` parameter has a quality of `1`.
+///
+/// The following entries are dropped:
+/// - Ones with a quality of `0`, which mark a language as not acceptable.
+/// - The `*` wildcard, which doesn't name a language.
+/// - Ones with a malformed quality value, such as `q=high` or `q=1.5`.
+/// - Empty entries, like those produced by a trailing comma.
+///
+/// Whitespace around entries, tags, and parameters is ignored.
+///
+/// # Example
+///
+/// ```
+/// use sys_locale::parse_accept_language;
+///
+/// let tags = parse_accept_language("fr-CH, fr;q=0.9, en;q=0.8, de;q=0.7, *;q=0.5");
+/// assert_eq!(tags, ["fr-CH", "fr", "en", "de"]);
+/// ```
+pub fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut entries: Vec<(u16, &str)> = header
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = parts.next()?.trim();
+            if tag.is_empty() || tag == "*" || tag.contains(char::is_whitespace) {
+                return None;
+            }
+
+            let mut quality = 1000;
+            for param in parts {
+                let (name, value) = param.split_once('=')?;
+                if name.trim().eq_ignore_ascii_case("q") {
+                    quality = parse_quality(value.trim())?;
+                }
+            }
+
+            Some((quality, tag)).filter(|(quality, _)| *quality != 0)
+        })
+        .collect();
+
+    // `sort_by` is stable, so entries with the same quality keep their order.
+    entries.sort_by(|(a, _), (b, _)| b.cmp(a));
+    entries
+        .into_iter()
+        .map(|(_, tag)| String::from(tag))
+        .collect()
+}
+
+/// Parses a `qvalue` from RFC 9110 into thousandths, so `0.85` becomes `850`.
+///
+/// ```text
+/// qvalue = ( "0" [ "." 0*3DIGIT ] ) / ( "1" [ "." 0*3("0") ] )
+/// ```
+fn parse_quality(value: &str) -> Option<u16> {
+    let (whole, fraction) = match value.split_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (value, ""),
+    };
+    if fraction.len() > 3 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut thousandths: u16 = 0;
+    for (i, digit) in fraction.bytes().enumerate() {
+        thousandths += u16::from(digit - b'0') * [100, 10, 1][i];
+    }
+
+    match whole {
+        "0" => Some(thousandths),
+        "1" if thousandths == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_accept_language, parse_quality};
+
+    #[test]
+    fn quality() {
+        assert_eq!(parse_quality("1"), Some(1000));
+        assert_eq!(parse_quality("1.000"), Some(1000));
+        assert_eq!(parse_quality("0"), Some(0));
+        assert_eq!(parse_quality("0.5"), Some(500));
+        assert_eq!(parse_quality("0.85"), Some(850));
+        assert_eq!(parse_quality("0.001"), Some(1));
+        assert_eq!(parse_quality("0."), Some(0));
+
+        assert_eq!(parse_quality(""), None);
+        assert_eq!(parse_quality("1.5"), None);
+        assert_eq!(parse_quality("2"), None);
+        assert_eq!(parse_quality("0.1234"), None);
+        assert_eq!(parse_quality("high"), None);
+        assert_eq!(parse_quality("-0.5"), None);
+    }
+
+    #[test]
+    fn parse_header() {
+        // Missing q
+        assert_eq!(parse_accept_language("en-US"), ["en-US"]);
+        assert_eq!(parse_accept_language("en-US, fr"), ["en-US", "fr"]);
+
+        // Sorted by q, stable for equal q
+        assert_eq!(
+            parse_accept_language("de;q=0.5, en-US, fr;q=0.8, es;q=0.5, it"),
+            ["en-US", "it", "fr", "de", "es"]
+        );
+
+        // q=0 and wildcards are dropped
+        assert_eq!(
+            parse_accept_language("en;q=0, fr, *;q=0.1, de;q=0.000"),
+            ["fr"]
+        );
+
+        // Malformed q values drop the entry
+        assert_eq!(
+            parse_accept_language("en;q=high, fr;q=1.5, de;q, es;q=0.5"),
+            ["es"]
+        );
+
+        // Whitespace variations
+        assert_eq!(
+            parse_accept_language("  fr-CH ;  q = 0.9 ,en;Q=1,\tde ; q=0.7  "),
+            ["en", "fr-CH", "de"]
+        );
+
+        // Empty entries
+        assert!(parse_accept_language("").is_empty());
+        assert_eq!(parse_accept_language(",en,,fr,"), ["en", "fr"]);
+    }
+}
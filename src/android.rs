@@ -1,5 +1,10 @@
 use alloc::{string::String, vec};
-use core::convert::TryFrom;
+use core::{convert::TryFrom, ffi::c_void, fmt, fmt::Write};
+
+use crate::{
+    android_tags::{compose_configuration, modern_language},
+    LocaleList,
+};
 
 fn get_property(name: &'static [u8]) -> Option<String> {
     let mut value = vec![0u8; libc::PROP_VALUE_MAX as usize];
@@ -35,7 +40,8 @@ fn read_locale() -> Option<String> {
     }
 
     // Android 4.0 and below
-    if let Some(mut language) = get_property(LANG_KEY) {
+    if let Some(language) = get_property(LANG_KEY) {
+        let mut language = modern_language(language);
         // The details of this functionality are not publically available, so this is just
         // adapted "best effort" from the original code.
         match get_property(COUNTRY_KEY) {
@@ -70,6 +76,64 @@ fn read_locale() -> Option<String> {
     }
 }
 
-pub(crate) fn get() -> impl Iterator<Item = String> {
-    read_locale().into_iter()
+/// An opaque `AConfiguration` from the NDK.
+#[repr(C)]
+struct AConfiguration {
+    _private: [u8; 0],
+}
+
+// The output buffers are `char*` in C, which are passed the same way as byte pointers.
+#[link(name = "android")]
+extern "C" {
+    fn AConfiguration_getLanguage(config: *mut AConfiguration, out_language: *mut u8);
+    fn AConfiguration_getCountry(config: *mut AConfiguration, out_country: *mut u8);
+}
+
+/// Reads the language and country of an NDK `AConfiguration`.
+///
+/// # Safety
+///
+/// `config` must point to a valid `AConfiguration`.
+pub(crate) unsafe fn get_from_configuration(config: *mut c_void) -> Option<String> {
+    let config = config.cast::<AConfiguration>();
+    let mut language = [0u8; 2];
+    let mut country = [0u8; 2];
+    // SAFETY: The caller guarantees `config` is valid, and both functions write exactly two
+    // bytes, without a NUL terminator.
+    AConfiguration_getLanguage(config, language.as_mut_ptr());
+    AConfiguration_getCountry(config, country.as_mut_ptr());
+    compose_configuration(language, country)
+}
+
+const TIMEZONE_KEY: &[u8] = b"persist.sys.timezone\0";
+
+/// Returns the IANA id of the time zone set in the system settings.
+pub(crate) fn get_timezone() -> Option<String> {
+    get_property(TIMEZONE_KEY)
+}
+
+pub(crate) fn explain(report: &mut String) -> fmt::Result {
+    writeln!(report, "Provider: Android system properties")?;
+    for key in [
+        LOCALE_KEY,
+        LANG_KEY,
+        COUNTRY_KEY,
+        LOCALEVAR_KEY,
+        PRODUCT_LOCALE_KEY,
+        PRODUCT_LANGUAGE_KEY,
+        PRODUCT_REGION_KEY,
+    ] {
+        // Every key ends with a NUL terminator, which isn't part of the name.
+        let name = core::str::from_utf8(&key[..key.len() - 1]).unwrap_or_default();
+        writeln!(report, "  {} = {:?}", name, get_property(key))?;
+    }
+    Ok(())
+}
+
+pub(crate) const SUPPORTED: bool = true;
+
+pub(crate) type Locales = core::option::IntoIter<String>;
+
+pub(crate) fn get() -> LocaleList {
+    LocaleList::new(read_locale().into_iter())
 }
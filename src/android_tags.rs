@@ -0,0 +1,62 @@
+use alloc::string::String;
+
+use crate::locale::LANGUAGE_ALIASES;
+
+/// Replaces the deprecated language codes Java's `Locale` reports, like `iw` for Hebrew, with
+/// their current ones.
+///
+/// Releases that predate `Locale.toLanguageTag` store the result of `Locale.getLanguage` in
+/// their properties, which always uses the old codes for Hebrew, Indonesian, and Yiddish.
+pub(crate) fn modern_language(language: String) -> String {
+    LANGUAGE_ALIASES
+        .iter()
+        .find(|(deprecated, _)| language.eq_ignore_ascii_case(deprecated))
+        .map_or(language, |(_, preferred)| String::from(*preferred))
+}
+
+/// Builds a tag from the two character codes of an `AConfiguration`, which are zeroed when
+/// unset.
+pub(crate) fn compose_configuration(language: [u8; 2], country: [u8; 2]) -> Option<String> {
+    let code = |code: [u8; 2]| {
+        Some(code)
+            .filter(|code| code.iter().all(u8::is_ascii_alphabetic))
+            .and_then(|code| core::str::from_utf8(&code).ok().map(String::from))
+    };
+
+    // Resource configurations use the same deprecated codes as Java's `Locale`.
+    let mut tag = modern_language(code(language)?);
+    if let Some(country) = code(country) {
+        tag.push('-');
+        tag.push_str(&country);
+    }
+    Some(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compose_configuration, modern_language};
+    use alloc::string::String;
+
+    #[test]
+    fn composes_configuration() {
+        assert_eq!(
+            compose_configuration(*b"en", *b"US").as_deref(),
+            Some("en-US")
+        );
+        assert_eq!(compose_configuration(*b"fr", [0, 0]).as_deref(), Some("fr"));
+        assert_eq!(
+            compose_configuration(*b"iw", *b"IL").as_deref(),
+            Some("he-IL")
+        );
+        assert_eq!(compose_configuration([0, 0], *b"US"), None);
+        assert_eq!(compose_configuration([0, 0], [0, 0]), None);
+        assert_eq!(compose_configuration(*b"e\0", *b"US"), None);
+    }
+
+    #[test]
+    fn replaces_deprecated_languages() {
+        for (language, expected) in [("iw", "he"), ("in", "id"), ("ji", "yi"), ("en", "en")] {
+            assert_eq!(modern_language(String::from(language)), expected);
+        }
+    }
+}
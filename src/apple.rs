@@ -1,5 +1,7 @@
 use alloc::{string::String, vec::Vec};
-use core::ffi::c_void;
+use core::{ffi::c_void, fmt, fmt::Write};
+
+use crate::{apple_strings::written_len, posix::posix_to_bcp47, LocaleList};
 
 type CFIndex = isize;
 type Boolean = u8;
@@ -16,6 +18,11 @@ struct CFRange {
 }
 
 type CFTypeRef = *const c_void;
+#[cfg(target_os = "macos")]
+type CFTypeID = usize;
+type CFAllocatorRef = *const c_void;
+#[cfg(target_os = "macos")]
+type CFPropertyListRef = CFTypeRef;
 
 #[repr(C)]
 struct __CFArray(c_void);
@@ -25,6 +32,15 @@ type CFArrayRef = *const __CFArray;
 struct __CFString(c_void);
 type CFStringRef = *const __CFString;
 
+#[repr(C)]
+struct __CFLocale(c_void);
+type CFLocaleRef = *const __CFLocale;
+type CFLocaleKey = CFStringRef;
+
+#[repr(C)]
+struct __CFTimeZone(c_void);
+type CFTimeZoneRef = *const __CFTimeZone;
+
 // Most of these definitions come from `core-foundation-sys`, but we want this crate
 // to be `no_std` and `core-foundation-sys` isn't currently.
 #[link(name = "CoreFoundation", kind = "framework")]
@@ -45,95 +61,208 @@ extern "C" {
     ) -> CFIndex;
 
     fn CFRelease(cf: CFTypeRef);
+    #[cfg(target_os = "macos")]
+    fn CFGetTypeID(cf: CFTypeRef) -> CFTypeID;
+    #[cfg(target_os = "macos")]
+    fn CFArrayGetTypeID() -> CFTypeID;
+    #[cfg(target_os = "macos")]
+    fn CFStringGetTypeID() -> CFTypeID;
+    fn CFStringCreateWithBytes(
+        alloc: CFAllocatorRef,
+        bytes: *const u8,
+        numBytes: CFIndex,
+        encoding: CFStringEncoding,
+        isExternalRepresentation: Boolean,
+    ) -> CFStringRef;
 
     fn CFLocaleCopyPreferredLanguages() -> CFArrayRef;
+    fn CFLocaleCopyCurrent() -> CFLocaleRef;
+    fn CFLocaleGetValue(locale: CFLocaleRef, key: CFLocaleKey) -> CFTypeRef;
+    fn CFLocaleGetIdentifier(locale: CFLocaleRef) -> CFStringRef;
+    fn CFLocaleCreate(allocator: CFAllocatorRef, localeIdentifier: CFStringRef) -> CFLocaleRef;
+    fn CFLocaleCreateCanonicalLocaleIdentifierFromString(
+        allocator: CFAllocatorRef,
+        localeIdentifier: CFStringRef,
+    ) -> CFStringRef;
+    fn CFLocaleCopyDisplayNameForPropertyValue(
+        displayLocale: CFLocaleRef,
+        key: CFLocaleKey,
+        value: CFStringRef,
+    ) -> CFStringRef;
+
+    static kCFLocaleCountryCode: CFLocaleKey;
+    static kCFLocaleIdentifier: CFLocaleKey;
+
+    fn CFTimeZoneCopySystem() -> CFTimeZoneRef;
+    fn CFTimeZoneGetName(tz: CFTimeZoneRef) -> CFStringRef;
+
+    #[cfg(target_os = "macos")]
+    fn CFPreferencesCopyAppValue(key: CFStringRef, applicationID: CFStringRef)
+        -> CFPropertyListRef;
+}
+
+pub(crate) const SUPPORTED: bool = true;
+
+pub(crate) fn get() -> LocaleList {
+    let mut locales = Locales {
+        preferred: get_languages(),
+        idx: 0,
+        pending: None,
+    };
+
+    // Some sandbox configurations hand out a preferred languages array that is non-null but
+    // stale or empty, so none of its entries can be read. The current locale is still
+    // resolved correctly in that case, so it's used as a last resort before giving up.
+    locales.pending = locales.next_preferred();
+    if locales.pending.is_none() {
+        locales.pending = current_locale_identifier();
+    }
+
+    LocaleList::new(locales)
+}
+
+pub(crate) fn explain(report: &mut String) -> fmt::Result {
+    writeln!(report, "Provider: Core Foundation")?;
+    let preferred: Vec<String> = Locales {
+        preferred: get_languages(),
+        idx: 0,
+        pending: None,
+    }
+    .collect();
+    writeln!(report, "  CFLocaleCopyPreferredLanguages = {:?}", preferred)?;
+    writeln!(
+        report,
+        "  CFLocaleGetIdentifier(CFLocaleCopyCurrent()) = {:?}",
+        current_locale_raw_identifier()
+    )?;
+    writeln!(report, "  kCFLocaleCountryCode = {:?}", get_country_code())
 }
 
-pub(crate) fn get() -> impl Iterator<Item = String> {
-    let preferred_langs = get_languages();
-    let mut idx = 0;
+/// The entries of the preferred languages array, which is kept alive while iterating.
+pub(crate) struct Locales {
+    preferred: Option<(CFArray, CFIndex)>,
+    idx: CFIndex,
+    /// An entry that was read ahead of time, returned before continuing with the array.
+    pending: Option<String>,
+}
 
+impl Locales {
     #[allow(clippy::as_conversions)]
-    core::iter::from_fn(move || unsafe {
-        let (langs, num_langs) = preferred_langs.as_ref()?;
+    fn next_preferred(&mut self) -> Option<String> {
+        let (langs, num_langs) = self.preferred.as_ref()?;
 
         // 0 to N-1 inclusive
-        if idx >= *num_langs {
-            return None;
+        let locale = if self.idx < *num_langs {
+            // SAFETY: The current index has been checked that its still within bounds of the array.
+            // XXX: We don't retain the strings because we know we have total ownership of the backing array.
+            let locale = unsafe { CFArrayGetValueAtIndex(langs.0, self.idx) } as CFStringRef;
+            self.idx += 1;
+
+            // SAFETY: `locale` is a valid CFString pointer because the array will always contain a value.
+            unsafe { cfstring_to_string(locale) }
+        } else {
+            None
+        };
+
+        // Stop at the first entry that can't be read, and release the array early.
+        if locale.is_none() {
+            self.preferred = None;
         }
+        locale
+    }
+}
 
-        // SAFETY: The current index has been checked that its still within bounds of the array.
-        // XXX: We don't retain the strings because we know we have total ownership of the backing array.
-        let locale = CFArrayGetValueAtIndex(langs.0, idx) as CFStringRef;
-        idx += 1;
+impl Iterator for Locales {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.pending.take().or_else(|| self.next_preferred())
+    }
+}
 
-        // SAFETY: `locale` is a valid CFString pointer because the array will always contain a value.
-        let str_len = CFStringGetLength(locale);
+/// Copies the contents of a `CFString` into a Rust string.
+///
+/// # Safety
+///
+/// `string` must be a valid `CFString` pointer.
+#[allow(clippy::as_conversions)]
+unsafe fn cfstring_to_string(string: CFStringRef) -> Option<String> {
+    let str_len = CFStringGetLength(string);
 
-        let range = CFRange {
-            location: 0,
-            length: str_len,
-        };
+    let range = CFRange {
+        location: 0,
+        length: str_len,
+    };
 
-        let mut capacity = 0;
-        // SAFETY:
-        // - `locale` is a valid CFString
-        // - The supplied range is within the length of the string.
-        // - `capacity` is writable.
-        // Passing NULL and `0` is correct for the buffer to get the
-        // encoded output length.
-        CFStringGetBytes(
-            locale,
-            range,
-            kCFStringEncodingUTF8,
-            0,
-            false as Boolean,
-            core::ptr::null_mut(),
-            0,
-            &mut capacity,
-        );
-
-        // Guard against a zero-sized allocation, if that were to somehow occur.
-        if capacity == 0 {
-            return None;
-        }
+    let mut capacity = 0;
+    // SAFETY:
+    // - `string` is a valid CFString
+    // - The supplied range is within the length of the string.
+    // - `capacity` is writable.
+    // Passing NULL and `0` is correct for the buffer to get the
+    // encoded output length.
+    CFStringGetBytes(
+        string,
+        range,
+        kCFStringEncodingUTF8,
+        0,
+        false as Boolean,
+        core::ptr::null_mut(),
+        0,
+        &mut capacity,
+    );
 
-        // Note: This is the number of bytes (u8) that will be written to
-        // the buffer, not the number of codepoints they would contain.
-        let mut buffer = Vec::with_capacity(capacity as usize);
-
-        // SAFETY:
-        // - `locale` is a valid CFString
-        // - The supplied range is within the length of the string.
-        // - `buffer` is writable and has sufficent capacity to receive the data.
-        // - `maxBufLen` is correctly based on `buffer`'s available capacity.
-        // - `out_len` is writable.
-        let mut out_len = 0;
-        CFStringGetBytes(
-            locale,
-            range,
-            kCFStringEncodingUTF8,
-            0,
-            false as Boolean,
-            buffer.as_mut_ptr(),
-            capacity as CFIndex,
-            &mut out_len,
-        );
-
-        // Sanity check that both calls to `CFStringGetBytes`
-        // were equivalent. If they weren't, the system is doing
-        // something very wrong...
-        assert!(out_len <= capacity);
-
-        // SAFETY: The system has written `out_len` elements, so they are
-        // initialized and inside the buffer's capacity bounds.
-        buffer.set_len(out_len as usize);
-
-        // This should always contain UTF-8 since we told the system to
-        // write UTF-8 into the buffer, but the value is small enough that
-        // using `from_utf8_unchecked` isn't worthwhile.
-        String::from_utf8(buffer).ok()
-    })
+    // Guard against a zero-sized or negative allocation, if that were to somehow occur.
+    if capacity <= 0 {
+        return None;
+    }
+
+    // Note: This is the number of bytes (u8) that will be written to
+    // the buffer, not the number of codepoints they would contain.
+    let mut buffer = Vec::with_capacity(capacity as usize);
+
+    // SAFETY:
+    // - `string` is a valid CFString
+    // - The supplied range is within the length of the string.
+    // - `buffer` is writable and has sufficent capacity to receive the data.
+    // - `maxBufLen` is correctly based on `buffer`'s available capacity.
+    // - `out_len` is writable.
+    let mut out_len = 0;
+    CFStringGetBytes(
+        string,
+        range,
+        kCFStringEncodingUTF8,
+        0,
+        false as Boolean,
+        buffer.as_mut_ptr(),
+        capacity as CFIndex,
+        &mut out_len,
+    );
+
+    // Sanity check that both calls to `CFStringGetBytes`
+    // were equivalent. If they weren't, the system is doing
+    // something very wrong, and the string is skipped rather
+    // than aborting the process.
+    let len = written_len(capacity, out_len)?;
+
+    // SAFETY: The system has written `len` elements, so they are
+    // initialized and inside the buffer's capacity bounds.
+    buffer.set_len(len);
+
+    // This should always contain UTF-8 since we told the system to
+    // write UTF-8 into the buffer, but the value is small enough that
+    // using `from_utf8_unchecked` isn't worthwhile. If it somehow isn't,
+    // a replacement character is better than losing the locale entirely.
+    match String::from_utf8(buffer) {
+        Ok(string) => Some(string),
+        Err(e) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                "CoreFoundation returned a string that isn't valid UTF-8, decoding it lossily"
+            );
+            Some(String::from_utf8_lossy(e.as_bytes()).into_owned())
+        }
+    }
 }
 
 fn get_languages() -> Option<(CFArray, CFIndex)> {
@@ -156,6 +285,163 @@ fn get_languages() -> Option<(CFArray, CFIndex)> {
     }
 }
 
+/// Returns the identifier of the current locale, such as `en_US`, converted to BCP 47.
+fn current_locale_identifier() -> Option<String> {
+    // Locale identifiers use ICU's `en_US@calendar=japanese` form, which only needs the same
+    // cleanup as a POSIX locale.
+    current_locale_raw_identifier()
+        .and_then(|identifier| posix_to_bcp47(&identifier))
+        .filter(|identifier| !identifier.is_empty())
+}
+
+/// Returns the identifier of the current locale as Core Foundation reports it.
+fn current_locale_raw_identifier() -> Option<String> {
+    unsafe {
+        // SAFETY: This function is safe to call and has no invariants. The returned locale is
+        // owned by us.
+        let locale = CFLocaleCopyCurrent();
+        if locale.is_null() {
+            return None;
+        }
+        let locale = CFLocale(locale);
+
+        // SAFETY: `locale` is a valid CFLocale. The identifier is owned by the locale, which
+        // outlives its use here.
+        let identifier = CFLocaleGetIdentifier(locale.0);
+        if identifier.is_null() {
+            return None;
+        }
+
+        cfstring_to_string(identifier)
+    }
+}
+
+/// Returns the country set in the system's region settings, such as `JP`.
+///
+/// This is configured separately from the preferred languages, so it may not match the region
+/// of any of them.
+pub(crate) fn get_country_code() -> Option<String> {
+    unsafe {
+        // SAFETY: This function is safe to call and has no invariants. The returned locale is
+        // owned by us.
+        let locale = CFLocaleCopyCurrent();
+        if locale.is_null() {
+            return None;
+        }
+        let locale = CFLocale(locale);
+
+        // SAFETY: `locale` is a valid CFLocale and `kCFLocaleCountryCode` is a valid key. The
+        // value is owned by the locale, which outlives its use here.
+        let country = CFLocaleGetValue(locale.0, kCFLocaleCountryCode);
+        if country.is_null() {
+            return None;
+        }
+
+        // SAFETY: The value for `kCFLocaleCountryCode` is always a CFString.
+        cfstring_to_string(country.cast())
+    }
+}
+
+/// Returns the IANA id of the system time zone, such as `Europe/Paris`.
+pub(crate) fn get_timezone() -> Option<String> {
+    unsafe {
+        // SAFETY: This function is safe to call and has no invariants. The returned time zone
+        // is owned by us.
+        let zone = CFTimeZoneCopySystem();
+        if zone.is_null() {
+            return None;
+        }
+        let zone = CFTimeZone(zone);
+
+        // SAFETY: `zone` is a valid CFTimeZone. The name is owned by the time zone, which
+        // outlives its use here.
+        let name = CFTimeZoneGetName(zone.0);
+        if name.is_null() {
+            return None;
+        }
+
+        cfstring_to_string(name).filter(|name| !name.is_empty())
+    }
+}
+
+/// Returns the name of the locale `tag` in the language of `in_locale`, or of the current
+/// locale if it's [`None`].
+pub(crate) fn display_name(tag: &str, in_locale: Option<&str>) -> Option<String> {
+    let tag = CFString::new(tag)?;
+
+    unsafe {
+        // SAFETY: `tag` is a valid CFString and a null allocator selects the default one. The
+        // returned identifier is owned by us.
+        let identifier =
+            CFLocaleCreateCanonicalLocaleIdentifierFromString(core::ptr::null(), tag.0);
+        if identifier.is_null() {
+            return None;
+        }
+        let identifier = CFString(identifier);
+
+        // SAFETY: Both functions are safe to call with a valid CFString and a null allocator.
+        // The returned locale is owned by us.
+        let locale = match in_locale {
+            Some(in_locale) => {
+                let in_locale = CFString::new(in_locale)?;
+                CFLocaleCreate(core::ptr::null(), in_locale.0)
+            }
+            None => CFLocaleCopyCurrent(),
+        };
+        if locale.is_null() {
+            return None;
+        }
+        let locale = CFLocale(locale);
+
+        // SAFETY: `locale` is a valid CFLocale, `kCFLocaleIdentifier` is a valid key, and
+        // `identifier` is a valid CFString. The returned name is owned by us.
+        let name =
+            CFLocaleCopyDisplayNameForPropertyValue(locale.0, kCFLocaleIdentifier, identifier.0);
+        if name.is_null() {
+            return None;
+        }
+        let name = CFString(name);
+
+        cfstring_to_string(name.0).filter(|name| !name.is_empty())
+    }
+}
+
+/// Returns the first entry of the `AppleLanguages` preference of the application `bundle_id`.
+///
+/// Core Foundation searches the application's own preferences before the global domain, so
+/// this falls back to the system's preferred languages like the application itself would.
+#[cfg(target_os = "macos")]
+pub(crate) fn get_locale_for_bundle(bundle_id: &str) -> Option<String> {
+    let key = CFString::new("AppleLanguages")?;
+    let application = CFString::new(bundle_id)?;
+
+    unsafe {
+        // SAFETY: Both arguments are valid CFStrings. The returned value is owned by us.
+        let value = CFPreferencesCopyAppValue(key.0, application.0);
+        if value.is_null() {
+            return None;
+        }
+        let value = CFPropertyList(value);
+
+        // SAFETY: `value` is a valid property list object. Any application can write any type
+        // of value to the preference, so it has to be checked before use.
+        if CFGetTypeID(value.0) != CFArrayGetTypeID() {
+            return None;
+        }
+        let languages: CFArrayRef = value.0.cast();
+
+        // SAFETY: `languages` is a valid CFArray, which is owned by `value` and outlives its
+        // use here. Every index is within its bounds.
+        (0..CFArrayGetCount(languages)).find_map(|idx| {
+            let language = CFArrayGetValueAtIndex(languages, idx);
+            if language.is_null() || CFGetTypeID(language) != CFStringGetTypeID() {
+                return None;
+            }
+            cfstring_to_string(language.cast()).filter(|language| !language.is_empty())
+        })
+    }
+}
+
 struct CFArray(CFArrayRef);
 
 impl Drop for CFArray {
@@ -164,3 +450,64 @@ impl Drop for CFArray {
         unsafe { CFRelease(self.0.cast()) }
     }
 }
+
+struct CFLocale(CFLocaleRef);
+
+impl Drop for CFLocale {
+    fn drop(&mut self) {
+        // SAFETY: This wrapper contains a valid CFLocale.
+        unsafe { CFRelease(self.0.cast()) }
+    }
+}
+
+struct CFTimeZone(CFTimeZoneRef);
+
+impl Drop for CFTimeZone {
+    fn drop(&mut self) {
+        // SAFETY: This wrapper contains a valid CFTimeZone.
+        unsafe { CFRelease(self.0.cast()) }
+    }
+}
+
+struct CFString(CFStringRef);
+
+impl CFString {
+    /// Creates a `CFString` holding a copy of `string`.
+    #[allow(clippy::as_conversions)]
+    fn new(string: &str) -> Option<Self> {
+        // SAFETY: `string` is valid for reads of its length and is UTF-8. A null allocator
+        // selects the default one. The returned string is owned by us.
+        let string = unsafe {
+            CFStringCreateWithBytes(
+                core::ptr::null(),
+                string.as_ptr(),
+                string.len() as CFIndex,
+                kCFStringEncodingUTF8,
+                false as Boolean,
+            )
+        };
+        if string.is_null() {
+            None
+        } else {
+            Some(Self(string))
+        }
+    }
+}
+
+impl Drop for CFString {
+    fn drop(&mut self) {
+        // SAFETY: This wrapper contains a valid CFString.
+        unsafe { CFRelease(self.0.cast()) }
+    }
+}
+
+#[cfg(target_os = "macos")]
+struct CFPropertyList(CFPropertyListRef);
+
+#[cfg(target_os = "macos")]
+impl Drop for CFPropertyList {
+    fn drop(&mut self) {
+        // SAFETY: This wrapper contains a valid property list object.
+        unsafe { CFRelease(self.0) }
+    }
+}
@@ -0,0 +1,27 @@
+/// Returns how many bytes `CFStringGetBytes` wrote into a buffer of `capacity` bytes, or
+/// [`None`] if the reported length can't be right.
+///
+/// Both lengths are `CFIndex` values, which are `isize`.
+#[allow(clippy::as_conversions)]
+pub(crate) fn written_len(capacity: isize, written: isize) -> Option<usize> {
+    if (0..=capacity).contains(&written) {
+        Some(written as usize)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::written_len;
+
+    #[test]
+    fn checks_written_len() {
+        assert_eq!(written_len(8, 8), Some(8));
+        assert_eq!(written_len(8, 3), Some(3));
+        assert_eq!(written_len(8, 0), Some(0));
+        assert_eq!(written_len(8, 9), None);
+        assert_eq!(written_len(8, -1), None);
+        assert_eq!(written_len(0, 1), None);
+    }
+}
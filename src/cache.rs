@@ -0,0 +1,164 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use crate::spin::SpinLock;
+
+/// The resolved preference list, leaked so that it can be borrowed for `'static`.
+///
+/// A null pointer means that the list hasn't been computed yet, or was reset.
+static LOCALES: AtomicPtr<Vec<String>> = AtomicPtr::new(ptr::null_mut());
+
+/// The token last passed to [`cached_locale_with_token`], if any.
+static TOKEN: SpinLock<Option<u64>> = SpinLock::new(None);
+
+/// Returns the preferred locales for the system or application, computing them only once.
+///
+/// The first call resolves the list with [`get_locales`](crate::get_locales) and stores it
+/// for the lifetime of the process. Later calls return the same slice without querying the
+/// system again or allocating, which suits applications that treat the locale as fixed
+/// after startup.
+///
+/// Call [`reset_locales`] to force the list to be recomputed after a known settings change.
+///
+/// # Example
+///
+/// ```no_run
+/// use sys_locale::locales;
+///
+/// if let Some(locale) = locales().first() {
+///     println!("The most preferred locale is {}", locale);
+/// }
+/// ```
+pub fn locales() -> &'static [String] {
+    let current = LOCALES.load(Ordering::Acquire);
+    if !current.is_null() {
+        // SAFETY: Non-null pointers stored in `LOCALES` come from `Box::into_raw` and are
+        // never freed once published, so they are valid for the rest of the program.
+        return unsafe { &*current };
+    }
+
+    let fresh = Box::into_raw(Box::new(crate::get_locales().collect::<Vec<_>>()));
+    match LOCALES.compare_exchange(ptr::null_mut(), fresh, Ordering::AcqRel, Ordering::Acquire) {
+        // SAFETY: `fresh` was just published and will never be freed.
+        Ok(_) => unsafe { &*fresh },
+        Err(existing) => {
+            // Another thread published a list first, so ours was never shared and can be freed.
+            // SAFETY: `fresh` came from `Box::into_raw` above and nothing else references it.
+            drop(unsafe { Box::from_raw(fresh) });
+            // SAFETY: See above, published pointers are never freed.
+            unsafe { &*existing }
+        }
+    }
+}
+
+/// Returns the most preferred locale, computing the preferences only once.
+///
+/// This is the first entry of [`locales`], for servers and other applications that look the
+/// locale up often, such as once per request, but don't expect it to change while they run.
+/// Unlike [`get_locale`](crate::get_locale), which queries the system on every call, this
+/// neither reads the environment nor allocates after the first call. Call [`reset_locales`]
+/// to pick up a changed setting.
+///
+/// # Example
+///
+/// ```no_run
+/// let locale = sys_locale::cached_locale().unwrap_or("en-US");
+/// println!("Responding in {}", locale);
+/// ```
+pub fn cached_locale() -> Option<&'static str> {
+    locales().first().map(String::as_str)
+}
+
+/// Returns the most preferred locale like [`cached_locale`], recomputing it whenever `token`
+/// differs from the one passed to the previous call.
+///
+/// This is for applications that already learn about settings changes themselves, such as from
+/// a platform notification they handle, and want to control the cache's lifetime without this
+/// crate subscribing to anything. Bump the token, like a counter, only when the settings have
+/// actually changed, and pass the current value on every lookup. The first call always
+/// recomputes, since there's no previous token to compare to.
+///
+/// This shares its storage with [`locales`] and [`cached_locale`], so a changed token also
+/// refreshes what they return. As with [`reset_locales`], every change of token leaks the
+/// previous list, so a value that changes on its own, like a timestamp, leaks a list per
+/// lookup and must not be used as the token.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// static SETTINGS_GENERATION: AtomicU64 = AtomicU64::new(0);
+///
+/// // Called by the application's own handler for settings changes.
+/// fn on_settings_changed() {
+///     SETTINGS_GENERATION.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// let generation = SETTINGS_GENERATION.load(Ordering::Relaxed);
+/// let locale = sys_locale::cached_locale_with_token(generation).unwrap_or("en-US");
+/// ```
+pub fn cached_locale_with_token(token: u64) -> Option<&'static str> {
+    let changed = TOKEN.with(|last| {
+        let changed = *last != Some(token);
+        *last = Some(token);
+        changed
+    });
+    if changed {
+        reset_locales();
+    }
+    cached_locale()
+}
+
+/// Discards the list stored by [`locales`], so that the next call resolves it again.
+///
+/// Slices returned by earlier calls to [`locales`] stay valid; the memory backing them is
+/// intentionally leaked instead of freed. This is only meant to be called in response to a
+/// settings change, so the leak is bounded by how often that happens.
+///
+/// This crate doesn't watch for settings changes itself, so the cache never goes stale on its
+/// own. Applications that want it to follow the settings call this from the notification their
+/// platform sends: `NSCurrentLocaleDidChangeNotification` on Apple platforms, `WM_SETTINGCHANGE`
+/// with the `intl` parameter on Windows, or `ACTION_LOCALE_CHANGED` on Android.
+pub fn reset_locales() {
+    // The old list is deliberately not freed, since `'static` borrows of it may still exist.
+    LOCALES.store(ptr::null_mut(), Ordering::Release);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cached_locale, cached_locale_with_token, locales, reset_locales, LOCALES};
+    use core::sync::atomic::Ordering;
+
+    #[test]
+    fn stores_and_resets() {
+        // The published list tells a recomputed list from a cached one. The slices' own
+        // pointers can't, since every empty list shares the same dangling pointer.
+        let published = || LOCALES.load(Ordering::Acquire);
+
+        let first = locales();
+        let first_list = published();
+        assert_eq!(first.as_ptr(), locales().as_ptr());
+        assert_eq!(first_list, published());
+
+        reset_locales();
+        let second = locales();
+        let second_list = published();
+        assert_eq!(first, second);
+        assert_ne!(first_list, second_list);
+
+        assert_eq!(cached_locale(), locales().first().map(|l| l.as_str()));
+
+        // Kept in the same test, since resetting would race with the checks above.
+        cached_locale_with_token(1);
+        let third_list = published();
+        assert_ne!(second_list, third_list);
+        cached_locale_with_token(1);
+        assert_eq!(third_list, published());
+        assert_eq!(cached_locale_with_token(2), cached_locale());
+        assert_ne!(third_list, published());
+    }
+}
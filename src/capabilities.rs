@@ -0,0 +1,105 @@
+/// Which functions of this crate query the platform on the current target, as returned by
+/// [`capabilities`].
+///
+/// Functions whose capability is `false` either don't exist on the target or always return
+/// [`None`], or the same value as a more general function, so applications can hide features
+/// that depend on them instead of calling them for nothing. A `true` value only means the
+/// platform is queried, not that it has a value configured.
+///
+/// Everything here is known at compile time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// [`get_locales`](crate::get_locales) queries the platform, the same as
+    /// [`SUPPORTED`](crate::SUPPORTED).
+    pub locales: bool,
+    /// [`get_locales`](crate::get_locales) can return more than one locale. Android and Deno
+    /// only report a single one.
+    pub multiple_locales: bool,
+    /// [`get_format_locale`](crate::get_format_locale) reads a setting of its own, instead of
+    /// returning the same locale as [`get_locale`](crate::get_locale).
+    pub format_locale: bool,
+    /// [`get_country_code`](crate::get_country_code) reads a setting of its own, instead of
+    /// taking the region of the format locale.
+    pub country_code: bool,
+    /// `get_geo_region` is available and reads the user's home location.
+    pub geo_region: bool,
+    /// [`get_timezone`](crate::get_timezone) queries the platform.
+    pub timezone: bool,
+    /// [`display_name`](crate::display_name) queries the platform.
+    pub display_name: bool,
+    /// `get_locale_metadata` is available and reads `LC_IDENTIFICATION`.
+    pub locale_metadata: bool,
+    /// `get_thread_locale` is available and reads the calling thread's languages.
+    pub thread_locale: bool,
+}
+
+/// Returns which functions of this crate query the platform on the current target.
+///
+/// [`SUPPORTED`](crate::SUPPORTED) tells whether the locale can be obtained at all. This goes
+/// into more detail, so that applications can degrade gracefully, such as by hiding a setting
+/// based on the user's home location where `get_geo_region` doesn't exist.
+///
+/// # Example
+///
+/// ```
+/// let capabilities = sys_locale::capabilities();
+/// if !capabilities.multiple_locales {
+///     println!("Only the most preferred locale is known");
+/// }
+/// ```
+pub const fn capabilities() -> Capabilities {
+    Capabilities {
+        locales: crate::SUPPORTED,
+        multiple_locales: cfg!(any(
+            all(unix, not(target_os = "android")),
+            all(
+                target_family = "wasm",
+                any(
+                    feature = "js",
+                    all(feature = "js-minimal", not(feature = "deno"))
+                ),
+                not(unix)
+            ),
+            windows
+        )),
+        format_locale: cfg!(any(
+            all(
+                unix,
+                not(any(target_vendor = "apple", target_os = "android"))
+            ),
+            windows
+        )),
+        country_code: cfg!(target_vendor = "apple"),
+        geo_region: cfg!(windows),
+        timezone: cfg!(any(unix, windows)),
+        display_name: cfg!(any(target_vendor = "apple", windows)),
+        locale_metadata: cfg!(all(
+            unix,
+            not(any(target_vendor = "apple", target_os = "android")),
+            target_env = "gnu"
+        )),
+        thread_locale: cfg!(windows),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::capabilities;
+
+    #[test]
+    fn matches_target() {
+        let capabilities = capabilities();
+        assert_eq!(capabilities.locales, crate::SUPPORTED);
+        assert!(!capabilities.multiple_locales || capabilities.locales);
+        assert!(!capabilities.format_locale || capabilities.locales);
+
+        #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+        {
+            assert!(capabilities.multiple_locales);
+            assert!(capabilities.format_locale);
+            assert!(!capabilities.display_name);
+            assert!(!capabilities.geo_region);
+        }
+    }
+}
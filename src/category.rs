@@ -0,0 +1,113 @@
+use alloc::{collections::BTreeMap, string::String};
+
+/// A locale category, as configured through an `LC_*` environment variable on Unix.
+///
+/// Besides the six categories defined by POSIX, this includes the ones glibc adds for
+/// addresses, names, and similar conventions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    /// Character classification and case conversion (`LC_CTYPE`).
+    Ctype,
+    /// Number formatting (`LC_NUMERIC`).
+    Numeric,
+    /// Date and time formatting (`LC_TIME`).
+    Time,
+    /// String collation (`LC_COLLATE`).
+    Collate,
+    /// Currency formatting (`LC_MONETARY`).
+    Monetary,
+    /// The language of messages and translations (`LC_MESSAGES`).
+    Messages,
+    /// The default paper size (`LC_PAPER`).
+    Paper,
+    /// The formatting of personal names (`LC_NAME`).
+    Name,
+    /// The formatting of postal addresses (`LC_ADDRESS`).
+    Address,
+    /// The formatting of telephone numbers (`LC_TELEPHONE`).
+    Telephone,
+    /// The measurement system (`LC_MEASUREMENT`).
+    Measurement,
+    /// Metadata about the locale itself (`LC_IDENTIFICATION`).
+    Identification,
+}
+
+impl Category {
+    /// Every category, in the order `locale` prints them.
+    pub const ALL: [Category; 12] = [
+        Category::Ctype,
+        Category::Numeric,
+        Category::Time,
+        Category::Collate,
+        Category::Monetary,
+        Category::Messages,
+        Category::Paper,
+        Category::Name,
+        Category::Address,
+        Category::Telephone,
+        Category::Measurement,
+        Category::Identification,
+    ];
+
+    /// Returns the name of the environment variable for this category, such as `LC_TIME`.
+    pub fn variable(self) -> &'static str {
+        match self {
+            Category::Ctype => "LC_CTYPE",
+            Category::Numeric => "LC_NUMERIC",
+            Category::Time => "LC_TIME",
+            Category::Collate => "LC_COLLATE",
+            Category::Monetary => "LC_MONETARY",
+            Category::Messages => "LC_MESSAGES",
+            Category::Paper => "LC_PAPER",
+            Category::Name => "LC_NAME",
+            Category::Address => "LC_ADDRESS",
+            Category::Telephone => "LC_TELEPHONE",
+            Category::Measurement => "LC_MEASUREMENT",
+            Category::Identification => "LC_IDENTIFICATION",
+        }
+    }
+}
+
+/// Returns the locale in effect for every category, as BCP 47 tags.
+///
+/// On Unix, this follows the same rules as `setlocale` and the `locale` command: a non-empty
+/// `LC_ALL` overrides every category, then each category's own `LC_*` variable applies, and
+/// `LANG` fills in the rest. Categories that end up with no locale, or with the `C` or `POSIX`
+/// locale, are left out of the map. Unlike [`get_locales`](crate::get_locales), `LANGUAGE`
+/// isn't consulted, since it isn't a category of its own.
+///
+/// Other platforms don't configure categories separately. There, [`Category::Messages`] maps
+/// to [`get_locale`](crate::get_locale) and every other category to
+/// [`get_format_locale`](crate::get_format_locale).
+///
+/// # Example
+///
+/// ```no_run
+/// use sys_locale::{get_category_locales, Category};
+///
+/// let categories = get_category_locales();
+/// if let Some(time) = categories.get(&Category::Time) {
+///     println!("Dates are formatted for {}", time);
+/// }
+/// ```
+pub fn get_category_locales() -> BTreeMap<Category, String> {
+    #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+    {
+        crate::unix::get_categories()
+    }
+    #[cfg(not(all(unix, not(any(target_vendor = "apple", target_os = "android")))))]
+    {
+        let messages = crate::get_locale();
+        let format = crate::get_format_locale();
+        Category::ALL
+            .iter()
+            .filter_map(|category| {
+                let locale = match category {
+                    Category::Messages => messages.clone(),
+                    _ => format.clone(),
+                };
+                locale.map(|locale| (*category, locale))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,60 @@
+use alloc::string::String;
+use core::{fmt, fmt::Write};
+
+use js_sys::{Function, Object};
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::{locale::normalize_reported, posix::posix_to_bcp47, reflect::property, LocaleList};
+
+/// Reads `LANG` through `Deno.env.get`.
+///
+/// This throws if the module wasn't granted `--allow-env`, which is treated the same as the
+/// variable being unset.
+fn env_lang(global: &Object) -> Option<String> {
+    let env = property(&property(global, "Deno")?, "env")?;
+    let get = property(&env, "get")?.dyn_into::<Function>().ok()?;
+
+    get.call1(&env, &JsValue::from_str("LANG"))
+        .ok()?
+        .as_string()
+        .filter(|lang| !lang.is_empty())
+        .and_then(|lang| posix_to_bcp47(&lang))
+}
+
+/// Reads `navigator.language` from the Deno global, which is already a BCP 47 tag, normalized
+/// like the tags of the native providers.
+fn navigator_language(global: &Object) -> Option<String> {
+    property(&property(global, "navigator")?, "language")?
+        .as_string()
+        .and_then(|lang| normalize_reported(&lang))
+}
+
+pub(crate) const SUPPORTED: bool = true;
+
+pub(crate) type Locales = core::option::IntoIter<String>;
+
+pub(crate) fn get() -> LocaleList {
+    // Deno has no `window` (and its `self` isn't a `WorkerGlobalScope`), so everything is
+    // reached through reflection on `globalThis` instead of the typed `web-sys` bindings.
+    let global = js_sys::global();
+    LocaleList::new(
+        env_lang(&global)
+            .or_else(|| navigator_language(&global))
+            .into_iter(),
+    )
+}
+
+pub(crate) fn explain(report: &mut String) -> fmt::Result {
+    let global = js_sys::global();
+    writeln!(report, "Provider: Deno")?;
+    writeln!(
+        report,
+        "  Deno.env.get(\"LANG\"), converted = {:?}",
+        env_lang(&global)
+    )?;
+    writeln!(
+        report,
+        "  navigator.language = {:?}",
+        navigator_language(&global)
+    )
+}
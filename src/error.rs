@@ -0,0 +1,41 @@
+use core::fmt;
+
+/// Why [`get_locale_result`](crate::get_locale_result) couldn't return a locale, or why
+/// [`validate_locales`](crate::validate_locales) rejected an entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LocaleError {
+    /// The current target has no way to obtain the locale, see [`SUPPORTED`](crate::SUPPORTED).
+    Unsupported,
+    /// The platform was queried successfully, but reported no locale.
+    NotFound,
+    /// The platform API failed with `code`, which is the value of `GetLastError` on Windows.
+    ///
+    /// On Windows, this can happen when group policy restricts access to the language
+    /// settings, among other reasons.
+    ProviderError {
+        /// The error code reported by the operating system.
+        code: u32,
+    },
+    /// The entry isn't a well-formed BCP 47 language tag, like `en_US` or `en--US`.
+    Malformed,
+    /// The entry is well-formed, but [undetermined](crate::Locale::is_undetermined), so it
+    /// doesn't name a language.
+    Undetermined,
+}
+
+impl fmt::Display for LocaleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocaleError::Unsupported => {
+                f.write_str("obtaining the locale isn't supported on this target")
+            }
+            LocaleError::NotFound => f.write_str("the system reported no locale"),
+            LocaleError::ProviderError { code } => {
+                write!(f, "the system locale API failed with error {}", code)
+            }
+            LocaleError::Malformed => f.write_str("the locale isn't a well-formed BCP 47 tag"),
+            LocaleError::Undetermined => f.write_str("the locale doesn't name a language"),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use alloc::{boxed::Box, string::String, sync::Arc};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use crate::spin::SpinLock;
+
+/// A unit of blocking work handed to the function registered with [`set_blocking_spawner`].
+pub type BlockingJob = Box<dyn FnOnce() + Send + 'static>;
+
+static SPAWNER: SpinLock<Option<fn(BlockingJob)>> = SpinLock::new(None);
+
+/// Whether the current provider may block, such as by reading a file.
+///
+/// Every other provider answers from memory or a quick system call, so handing the lookup to
+/// another thread would cost more than doing it in place.
+const BLOCKING: bool = cfg!(all(
+    any(feature = "kde", feature = "locale-conf"),
+    not(feature = "strict"),
+    unix,
+    not(any(target_vendor = "apple", target_os = "android"))
+));
+
+/// Registers the function [`get_locale_async`] uses to run lookups that may block.
+///
+/// The function must run the job it's given to completion on a thread where blocking is
+/// acceptable, such as a dedicated thread pool. With Tokio, this is done with
+/// `spawn_blocking`:
+///
+/// ```ignore
+/// sys_locale::set_blocking_spawner(|job| {
+///     tokio::task::spawn_blocking(job);
+/// });
+/// ```
+///
+/// Only the last registered function is used. If a job is dropped without being run, the
+/// future waiting on it resolves to [`None`].
+pub fn set_blocking_spawner(spawn: fn(BlockingJob)) {
+    SPAWNER.with(|spawner| *spawner = Some(spawn));
+}
+
+/// Returns the most preferred locale without blocking the calling task.
+///
+/// This resolves to the same value as [`get_locale`](crate::get_locale). On platforms where the
+/// lookup may block, like Unix with the `kde` or `locale-conf` feature reading a file, the
+/// lookup is handed to the function registered with [`set_blocking_spawner`]. Everywhere else,
+/// or if no function is registered, the lookup is cheap enough to run when the future is first
+/// polled.
+///
+/// This doesn't depend on any particular async runtime. Only available with the `async`
+/// feature.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example() {
+/// let locale = sys_locale::get_locale_async().await;
+/// println!("The locale is {:?}", locale);
+/// # }
+/// ```
+pub fn get_locale_async() -> impl Future<Output = Option<String>> {
+    Lookup { shared: None }
+}
+
+enum State {
+    Pending(Option<Waker>),
+    Done(Option<String>),
+}
+
+type Shared = Arc<SpinLock<State>>;
+
+struct Lookup {
+    /// The state shared with the spawned job, once the lookup has been handed off.
+    shared: Option<Shared>,
+}
+
+impl Future for Lookup {
+    type Output = Option<String>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let shared = match &self.shared {
+            Some(shared) => shared.clone(),
+            None => {
+                let spawn = match SPAWNER.with(|spawner| *spawner) {
+                    Some(spawn) if BLOCKING => spawn,
+                    _ => return Poll::Ready(crate::get_locale()),
+                };
+
+                let shared: Shared = Arc::new(SpinLock::new(State::Pending(None)));
+                let sender = Sender {
+                    shared: shared.clone(),
+                    sent: false,
+                };
+                spawn(Box::new(move || sender.send(crate::get_locale())));
+                self.shared = Some(shared.clone());
+                shared
+            }
+        };
+
+        shared.with(|state| match state {
+            State::Pending(waker) => {
+                *waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            State::Done(locale) => Poll::Ready(locale.take()),
+        })
+    }
+}
+
+/// Completes a [`Lookup`] from the spawned job, or with [`None`] if the job is dropped first.
+struct Sender {
+    shared: Shared,
+    sent: bool,
+}
+
+impl Sender {
+    fn send(mut self, locale: Option<String>) {
+        self.complete(locale);
+    }
+
+    fn complete(&mut self, locale: Option<String>) {
+        self.sent = true;
+        let waker =
+            self.shared.with(
+                |state| match core::mem::replace(state, State::Done(locale)) {
+                    State::Pending(waker) => waker,
+                    State::Done(_) => None,
+                },
+            );
+        // Wake outside of the lock, since the waker may poll the future right away.
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        if !self.sent {
+            self.complete(None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lookup, Sender, Shared, State};
+    use crate::spin::SpinLock;
+    use alloc::{string::String, sync::Arc, task::Wake};
+    use core::{
+        future::Future,
+        pin::Pin,
+        sync::atomic::{AtomicBool, Ordering},
+        task::{Context, Poll, Waker},
+    };
+
+    struct Flag(AtomicBool);
+
+    impl Wake for Flag {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn pending() -> (Lookup, Sender) {
+        let shared: Shared = Arc::new(SpinLock::new(State::Pending(None)));
+        let sender = Sender {
+            shared: shared.clone(),
+            sent: false,
+        };
+        (
+            Lookup {
+                shared: Some(shared),
+            },
+            sender,
+        )
+    }
+
+    #[test]
+    fn resolves_when_sent() {
+        let flag = Arc::new(Flag(AtomicBool::new(false)));
+        let waker = Waker::from(flag.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let (mut lookup, sender) = pending();
+        assert_eq!(Pin::new(&mut lookup).poll(&mut cx), Poll::Pending);
+
+        sender.send(Some(String::from("en-US")));
+        assert!(flag.0.load(Ordering::SeqCst));
+        assert_eq!(
+            Pin::new(&mut lookup).poll(&mut cx),
+            Poll::Ready(Some(String::from("en-US")))
+        );
+    }
+
+    #[test]
+    fn resolves_when_dropped() {
+        let waker = Waker::from(Arc::new(Flag(AtomicBool::new(false))));
+        let mut cx = Context::from_waker(&waker);
+
+        let (mut lookup, sender) = pending();
+        drop(sender);
+        assert_eq!(Pin::new(&mut lookup).poll(&mut cx), Poll::Ready(None));
+    }
+}
@@ -0,0 +1,79 @@
+use alloc::string::String;
+
+use crate::spin::SpinLock;
+
+/// A function returning the locale an application wants to use, if it has a preference.
+pub type LocaleHook = fn() -> Option<String>;
+
+static HOOK: SpinLock<Option<LocaleHook>> = SpinLock::new(None);
+
+/// Registers `hook` to be consulted by [`get_locales`](crate::get_locales) before the system.
+///
+/// This lets a framework apply its own locale policy, such as a setting read from its
+/// configuration system, to every function built on [`get_locales`](crate::get_locales),
+/// including [`get_locale`](crate::get_locale). When the hook returns a tag, it becomes the
+/// most preferred locale, followed by the system's preferences without it. When it returns
+/// [`None`] or an empty string, the system's preferences are returned unchanged.
+///
+/// The hook is global, and can only be set once: if a hook is already registered, it's kept,
+/// and `false` is returned. It may be called from any thread, possibly at the same time, so it
+/// must be thread-safe itself, and it shouldn't call back into this crate's lookup functions.
+/// Locales mocked with the `mock` feature take precedence over the hook.
+///
+/// # Example
+///
+/// ```
+/// fn configured_locale() -> Option<String> {
+///     // Read from the application's own settings
+///     Some(String::from("de-CH"))
+/// }
+///
+/// sys_locale::set_locale_hook(configured_locale);
+/// assert_eq!(sys_locale::get_locale().as_deref(), Some("de-CH"));
+/// ```
+pub fn set_locale_hook(hook: LocaleHook) -> bool {
+    HOOK.with(|current| match current {
+        Some(_) => false,
+        None => {
+            *current = Some(hook);
+            true
+        }
+    })
+}
+
+/// Puts the locale returned by the registered hook, if any, ahead of `locales`.
+pub(crate) fn prepend(locales: impl Iterator<Item = String>) -> impl Iterator<Item = String> {
+    // The hook is copied out first, so that it doesn't run while the lock is held.
+    let preferred = HOOK.with(|hook| *hook).and_then(|hook| hook());
+    put_first(preferred, locales)
+}
+
+/// Puts `preferred` ahead of `locales`, skipping its later occurrences.
+fn put_first(
+    preferred: Option<String>,
+    locales: impl Iterator<Item = String>,
+) -> impl Iterator<Item = String> {
+    let preferred = preferred.filter(|tag| !tag.is_empty());
+    let repeated = preferred.clone();
+    preferred
+        .into_iter()
+        .chain(locales.filter(move |locale| Some(locale) != repeated.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::put_first;
+    use alloc::{string::String, vec::Vec};
+
+    #[test]
+    fn puts_hook_locale_first() {
+        let put = |preferred: Option<&str>| {
+            let locales = ["fr-FR", "en-US"].iter().map(|l| String::from(*l));
+            put_first(preferred.map(String::from), locales).collect::<Vec<_>>()
+        };
+        assert_eq!(put(None), ["fr-FR", "en-US"]);
+        assert_eq!(put(Some("")), ["fr-FR", "en-US"]);
+        assert_eq!(put(Some("de-CH")), ["de-CH", "fr-FR", "en-US"]);
+        assert_eq!(put(Some("en-US")), ["en-US", "fr-FR"]);
+    }
+}
@@ -0,0 +1,107 @@
+use alloc::{string::String, vec::Vec};
+use core::{fmt, fmt::Write};
+
+use js_sys::{Array, Object};
+use wasm_bindgen::JsCast;
+
+use crate::{locale::normalize_reported, reflect::property, LocaleList};
+
+/// Reads `navigator.languages`, falling back to `navigator.language` for runtimes that only
+/// implement the latter.
+///
+/// The tags are normalized like the native providers' tags, so that repeats differing only in
+/// case or separator are skipped by [`LocaleList`]. Entries that aren't well-formed are dropped,
+/// as are the empty or `und` values of privacy-hardened browsers, so an undetermined
+/// `navigator.languages` falls through to `navigator.language`.
+fn navigator_languages(global: &Object) -> Vec<String> {
+    let navigator = match property(global, "navigator") {
+        Some(navigator) => navigator,
+        None => return Vec::new(),
+    };
+
+    let languages: Vec<String> = property(&navigator, "languages")
+        .and_then(|languages| languages.dyn_into::<Array>().ok())
+        .map(|languages| {
+            languages
+                .iter()
+                .filter_map(|l| l.as_string())
+                .filter_map(|l| normalize_reported(&l))
+                .collect()
+        })
+        .unwrap_or_default();
+    if !languages.is_empty() {
+        return languages;
+    }
+
+    property(&navigator, "language")
+        .and_then(|language| language.as_string())
+        .and_then(|language| normalize_reported(&language))
+        .into_iter()
+        .collect()
+}
+
+pub(crate) const SUPPORTED: bool = true;
+
+pub(crate) type Locales = alloc::vec::IntoIter<String>;
+
+pub(crate) fn get() -> LocaleList {
+    // `globalThis` is the window on the main thread and the `WorkerGlobalScope` in workers,
+    // both of which expose a `navigator`, so there's no need to tell them apart through the
+    // typed `web-sys` bindings.
+    LocaleList::new(navigator_languages(&js_sys::global()).into_iter())
+}
+
+pub(crate) fn explain(report: &mut String) -> fmt::Result {
+    writeln!(report, "Provider: js-sys reflection")?;
+    writeln!(
+        report,
+        "  navigator.languages, or navigator.language = {:?}",
+        navigator_languages(&js_sys::global())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::navigator_languages;
+    use crate::LocaleList;
+    use alloc::vec::Vec;
+    use js_sys::{Array, Object, Reflect};
+    use wasm_bindgen::JsValue;
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    /// Builds a global object whose `navigator` has the given `languages` and `language`.
+    fn stub_global(languages: &[&str], language: &str) -> Object {
+        let navigator = Object::new();
+        let list = Array::new();
+        for l in languages {
+            list.push(&JsValue::from_str(l));
+        }
+        Reflect::set(&navigator, &"languages".into(), &list).unwrap();
+        Reflect::set(&navigator, &"language".into(), &language.into()).unwrap();
+
+        let global = Object::new();
+        Reflect::set(&global, &"navigator".into(), &navigator).unwrap();
+        global
+    }
+
+    #[test]
+    fn normalizes_and_dedups_languages() {
+        let global = stub_global(&["en-US", "en", "en-US", "en_us", "", "de--DE"], "fr");
+        let locales: Vec<_> = LocaleList::new(navigator_languages(&global).into_iter()).collect();
+        assert_eq!(locales, ["en-US", "en"]);
+
+        let global = stub_global(&[], "fr_ca");
+        assert_eq!(navigator_languages(&global), ["fr-CA"]);
+    }
+
+    #[test]
+    fn drops_undetermined_languages() {
+        let global = stub_global(&["und", ""], "de-AT");
+        assert_eq!(navigator_languages(&global), ["de-AT"]);
+
+        let global = stub_global(&["und"], "");
+        assert!(navigator_languages(&global).is_empty());
+        let global = stub_global(&[""], "und");
+        assert!(navigator_languages(&global).is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use std::fs;
+
+use crate::{
+    posix::posix_to_bcp47,
+    unix_env::{LANG, LANGUAGE},
+};
+
+/// The locale settings KDE Plasma stores in `plasma-localerc`, still in POSIX form.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Settings {
+    /// `LANGUAGE` from the `[Translations]` group, a `:` separated list of locales.
+    pub(crate) language: Option<String>,
+    /// `LANG` from the `[Formats]` group, the locale used for regional formats.
+    pub(crate) formats: Option<String>,
+}
+
+impl Settings {
+    /// Returns the translation languages followed by the formats locale, converted to BCP 47
+    /// and without duplicates, like the environment variables they stand in for.
+    ///
+    /// The values of the variables named in `excluded`, `LANGUAGE` or `LANG`, are skipped, the
+    /// same as for the environment.
+    pub(crate) fn locales(&self, excluded: &[&str]) -> Vec<String> {
+        let language = self
+            .language
+            .iter()
+            .filter(|_| !excluded.contains(&LANGUAGE))
+            .flat_map(|language| language.split(':'));
+        let formats = self
+            .formats
+            .as_deref()
+            .filter(|_| !excluded.contains(&LANG));
+        let mut locales: Vec<String> = Vec::new();
+        for locale in language.chain(formats).filter_map(posix_to_bcp47) {
+            if !locales.contains(&locale) {
+                locales.push(locale);
+            }
+        }
+        locales
+    }
+
+    /// Returns the formats locale, converted to BCP 47.
+    pub(crate) fn format(&self) -> Option<String> {
+        self.formats.as_deref().and_then(posix_to_bcp47)
+    }
+}
+
+/// Reads the settings from `plasma-localerc` in the user's configuration directory.
+///
+/// Returns [`None`] if the file doesn't exist or can't be read, which is the case for anyone
+/// not using Plasma.
+pub(crate) fn read() -> Option<Settings> {
+    fs::read_to_string(crate::unix::config_dir()?.join("plasma-localerc"))
+        .ok()
+        .map(|contents| parse(&contents))
+}
+
+/// Parses the contents of a `plasma-localerc` file, which uses KDE's INI-like `KConfig` format.
+fn parse(contents: &str) -> Settings {
+    let mut settings = Settings::default();
+    let mut group = "";
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            group = name;
+            continue;
+        }
+
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+        // Keys may carry flags like `LANG[$i]`, which don't matter for reading the value.
+        let key = key.split('[').next().unwrap_or(key).trim_end();
+        if value.is_empty() {
+            continue;
+        }
+
+        match (group, key) {
+            ("Translations", "LANGUAGE") => settings.language = Some(String::from(value)),
+            ("Formats", "LANG") => settings.formats = Some(String::from(value)),
+            _ => {}
+        }
+    }
+
+    settings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Settings};
+
+    #[test]
+    fn parses_settings() {
+        let settings = parse(
+            "[Formats]\n\
+             LANG=de_DE.UTF-8\n\
+             LC_TIME=en_GB.UTF-8\n\
+             \n\
+             # Comment\n\
+             [Translations]\n\
+             LANGUAGE=de:en_US\n",
+        );
+        assert_eq!(settings.language.as_deref(), Some("de:en_US"));
+        assert_eq!(settings.formats.as_deref(), Some("de_DE.UTF-8"));
+
+        // Keys only count in their own group
+        let settings = parse("[Translations]\nLANG=fr_FR\n[Formats]\nLANGUAGE=fr\n");
+        assert_eq!(settings, Settings::default());
+
+        // Whitespace, flags, and empty values
+        let settings = parse("  [Formats]\nLANG[$i] = pt_BR.UTF-8 \n[Translations]\nLANGUAGE=\n");
+        assert_eq!(settings.formats.as_deref(), Some("pt_BR.UTF-8"));
+        assert_eq!(settings.language, None);
+
+        assert_eq!(parse(""), Settings::default());
+    }
+
+    #[test]
+    fn converts_settings() {
+        let settings = Settings {
+            language: Some(String::from("de:en_US:C:de_DE")),
+            formats: Some(String::from("de_DE.UTF-8")),
+        };
+        assert_eq!(settings.locales(&[]), ["de", "en-US", "de-DE"]);
+        assert_eq!(settings.locales(&["LANGUAGE"]), ["de-DE"]);
+        assert_eq!(settings.locales(&["LANG"]), ["de", "en-US", "de-DE"]);
+        assert!(settings.locales(&["LANGUAGE", "LANG"]).is_empty());
+        assert_eq!(settings.format().as_deref(), Some("de-DE"));
+
+        let settings = Settings {
+            language: None,
+            formats: Some(String::from("C.UTF-8")),
+        };
+        assert!(settings.locales(&[]).is_empty());
+        assert_eq!(settings.format(), None);
+    }
+}
@@ -4,25 +4,148 @@
 //! - Android
 //! - iOS (and derivatives such as watchOS, tvOS, and visionOS)
 //! - macOS
-//! - Linux, BSD, and other UNIX variations
-//! - WebAssembly on the web (via the `js` feature)
+//! - Linux, BSD, illumos, Solaris, and other UNIX variations
+//! - Haiku (from its POSIX environment variables)
+//! - WebAssembly on the web (via the `js` or `js-minimal` feature)
+//! - WebAssembly under Deno (via the `deno` feature)
+//! - WebAssembly with Emscripten (from its emulated environment variables)
 //! - Windows
+//!
+//! # Web features
+//!
+//! The `js` feature reads `navigator.languages` through the typed `web-sys` bindings, which
+//! check that the global object really is a `Window` or `WorkerGlobalScope`. The `js-minimal`
+//! feature reads the same properties by reflection on the global object with only `js-sys`,
+//! which avoids the `web-sys` dependency and produces a smaller binary, at the cost of those
+//! checks. If both are enabled, `js` is used.
+//!
+//! The `deno` feature reads `LANG` and `navigator.language` under Deno, which only report one
+//! locale. It takes precedence over `js-minimal`, but `js` takes precedence over it, so that
+//! enabling `deno` somewhere in the dependency graph can't reduce a browser's list to a single
+//! entry.
+//!
+//! # Strict mode
+//!
+//! Enabling the `strict` feature guarantees that the locale is only ever read from the
+//! canonical platform source: the system API on Android, Apple platforms, Windows, and the
+//! web, and `LANGUAGE`, `LC_ALL`, `LC_*`, and `LANG` on Unix. Any additional input channel,
+//! such as an override variable, a configuration file, or a desktop settings service, is
+//! compiled out when it's enabled.
+//!
+//! This currently disables the configuration file fallbacks of the `kde` and `locale-conf`
+//! features, and protects deployments from new channels being picked up silently. Sources the
+//! application configures itself, like the override file of a [`Resolver`] on Unix, aren't
+//! implicit and remain available.
+//!
+//! # KDE Plasma
+//!
+//! Applications started from the Plasma menu don't always inherit a full locale environment.
+//! With the `kde` feature enabled, the Unix provider falls back to the `[Translations]` and
+//! `[Formats]` settings stored in `plasma-localerc` when no locale environment variable is
+//! set. The environment always takes precedence.
+//!
+//! # `locale.conf`
+//!
+//! With the `locale-conf` feature enabled, the Unix provider also falls back to the
+//! `locale.conf` files used by systemd based distributions when no locale environment variable
+//! is set, which helps applications started without a login session's environment. The
+//! user's `$XDG_CONFIG_HOME/locale.conf` (or `~/.config/locale.conf`) is read first, then
+//! Plasma's settings if the `kde` feature is enabled, and finally the system's
+//! `/etc/locale.conf`.
 #![cfg_attr(any(not(unix), target_vendor = "apple", target_os = "android"), no_std)]
 extern crate alloc;
 use alloc::string::String;
 
+mod accept_language;
+pub use accept_language::parse_accept_language;
+
+mod cache;
+pub use cache::{cached_locale, cached_locale_with_token, locales, reset_locales};
+
+mod capabilities;
+pub use capabilities::{capabilities, Capabilities};
+
+mod category;
+pub use category::{get_category_locales, Category};
+
+mod error;
+pub use error::LocaleError;
+
+mod locale;
+pub use locale::{
+    canonicalize, fallback_chain, is_canonical, is_valid_bcp47, matches_language, maximize,
+    minimize, set_region, set_script, to_posix, Fallbacks, Locale, LocaleBuilder, SubtagKind,
+    Subtags,
+};
+
+mod spin;
+
+mod hook;
+pub use hook::{set_locale_hook, LocaleHook};
+
+#[cfg(feature = "async")]
+mod future;
+#[cfg(feature = "async")]
+pub use future::{get_locale_async, set_blocking_spawner, BlockingJob};
+
+#[cfg(feature = "mock")]
+mod mock;
+#[cfg(feature = "mock")]
+pub use mock::{clear_mock_locales, set_mock_locales};
+
+mod subtag;
+pub use subtag::{Language, Region};
+
+mod negotiate;
+pub use negotiate::{
+    negotiate, preferred_position, prefers, sort_by_preference, supported_locales,
+};
+
+mod resolver;
+pub use resolver::Resolver;
+
+// The logic of each provider that doesn't call into the platform lives in a module of its own,
+// which the `test-providers` feature compiles on every target so that its tests run anywhere.
 #[cfg(target_os = "android")]
 mod android;
+#[cfg(any(target_os = "android", feature = "test-providers"))]
+#[cfg_attr(not(target_os = "android"), allow(dead_code))]
+mod android_tags;
 #[cfg(target_os = "android")]
 use android as provider;
 
 #[cfg(target_vendor = "apple")]
 mod apple;
+#[cfg(any(target_vendor = "apple", feature = "test-providers"))]
+#[cfg_attr(not(target_vendor = "apple"), allow(dead_code))]
+mod apple_strings;
 #[cfg(target_vendor = "apple")]
 use apple as provider;
 
+// Haiku is part of the `unix` family, so console applications get the locale from `LC_ALL`
+// and `LANG` like on any other UNIX. Haiku doesn't always export those to GUI applications,
+// which would need `BLocaleRoster` to be read through its C++ API instead.
+//
+// illumos and Solaris are also part of the `unix` family and use this provider. Their locale
+// names, like `en_US.ISO8859-1` or `ja_JP.eucJP`, only differ from other systems in the
+// codeset, which is dropped anyway. The system's defaults live in `/etc/default/init`, which
+// init exports to every process, so only the time zone needs a fallback to read it directly.
+//
+// `wasm32-unknown-emscripten` is part of both the `wasm` and `unix` families. It always uses
+// this provider, since Emscripten emulates the environment and fills in `LANG` from the
+// browser's `navigator.languages`. The `js` and `deno` providers below exclude `unix` targets
+// so that they can never be picked for it instead.
 #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
 mod unix;
+#[cfg(any(
+    all(unix, not(any(target_vendor = "apple", target_os = "android"))),
+    feature = "test-providers"
+))]
+#[cfg_attr(
+    not(all(unix, not(any(target_vendor = "apple", target_os = "android")))),
+    allow(dead_code)
+)]
+mod unix_env;
 #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
 use unix as provider;
 
@@ -31,21 +154,257 @@ mod wasm;
 #[cfg(all(target_family = "wasm", feature = "js", not(unix)))]
 use wasm as provider;
 
+// `js-minimal` only takes effect when neither `js` nor `deno` is enabled, and `deno` only when
+// `js` isn't, so that enabling either somewhere in the dependency graph can't replace the typed
+// `web-sys` bindings, which read the browser's whole list.
+#[cfg(all(
+    target_family = "wasm",
+    feature = "js-minimal",
+    not(any(feature = "js", feature = "deno")),
+    not(unix)
+))]
+mod js_minimal;
+#[cfg(all(
+    target_family = "wasm",
+    feature = "js-minimal",
+    not(any(feature = "js", feature = "deno")),
+    not(unix)
+))]
+use js_minimal as provider;
+
+#[cfg(all(
+    target_family = "wasm",
+    feature = "deno",
+    not(feature = "js"),
+    not(unix)
+))]
+mod deno;
+#[cfg(all(
+    target_family = "wasm",
+    feature = "deno",
+    not(feature = "js"),
+    not(unix)
+))]
+use deno as provider;
+
+// Shared by the providers reading the global object of a JavaScript runtime.
+#[cfg(all(
+    target_family = "wasm",
+    any(feature = "js", feature = "js-minimal", feature = "deno"),
+    not(unix)
+))]
+mod reflect;
+
+mod posix;
+pub use posix::posix_to_bcp47;
+
+// Plasma's settings only stand in for the environment variables read by the Unix provider.
+#[cfg(all(
+    feature = "kde",
+    not(feature = "strict"),
+    unix,
+    not(any(target_vendor = "apple", target_os = "android"))
+))]
+mod kde;
+
+// `locale.conf` only stands in for the environment variables read by the Unix provider.
+#[cfg(all(
+    feature = "locale-conf",
+    not(feature = "strict"),
+    unix,
+    not(any(target_vendor = "apple", target_os = "android"))
+))]
+mod locale_conf;
+
+#[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+mod metadata;
+#[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+pub use metadata::LocaleMetadata;
+
+#[cfg(any(windows, test))]
+mod windows_zones;
+
 #[cfg(windows)]
 mod windows;
+#[cfg(any(windows, feature = "test-providers"))]
+#[cfg_attr(not(windows), allow(dead_code))]
+mod windows_names;
 #[cfg(windows)]
 use windows as provider;
 
-#[cfg(not(any(unix, all(target_family = "wasm", feature = "js", not(unix)), windows)))]
+#[cfg(not(any(
+    unix,
+    all(
+        target_family = "wasm",
+        any(feature = "js", feature = "js-minimal", feature = "deno"),
+        not(unix)
+    ),
+    windows
+)))]
 mod provider {
-    pub fn get() -> impl Iterator<Item = alloc::string::String> {
-        core::iter::empty()
+    pub(crate) const SUPPORTED: bool = false;
+
+    pub(crate) type Locales = core::iter::Empty<alloc::string::String>;
+
+    pub(crate) fn get() -> crate::LocaleList {
+        crate::LocaleList::new(core::iter::empty())
+    }
+
+    pub(crate) fn explain(report: &mut alloc::string::String) -> core::fmt::Result {
+        use core::fmt::Write;
+        writeln!(report, "Provider: none, this target isn't supported")
+    }
+}
+
+/// Whether the current target has a way to obtain the locale.
+///
+/// This is `false` on targets without a locale provider, such as bare metal targets or the web
+/// without the `js`, `js-minimal`, or `deno` feature, where every function in this crate
+/// returns [`None`] or an empty list. Checking it lets applications fall back to their own
+/// configuration right away instead of having to interpret a missing locale at runtime.
+///
+/// A `true` value doesn't mean a locale will be found, only that the platform is queried.
+///
+/// # Example
+///
+/// ```
+/// if !sys_locale::SUPPORTED {
+///     println!("Using the configured locale, since the system one isn't available");
+/// }
+/// ```
+// Every provider declares this itself, so the fallback `provider` above, which has nothing to
+// query, is the only one that reports `false`.
+pub const SUPPORTED: bool = provider::SUPPORTED;
+
+/// The locales reported by the platform, in descending order of preference and without
+/// repeated entries.
+///
+/// On Unix, [`LocaleList::from_env`] resolves this from a captured environment instead of the
+/// current one.
+//
+// Every provider returns this from its `get()` function, wrapping its own iterator type
+// (`provider::Locales`), so that all of the public functions derive from the same list no
+// matter how a platform produces it.
+//
+// Providers report locales in their platform's preference order, but not all platforms
+// guarantee that a tag is only listed once, so repeated entries are skipped here. This keeps
+// the ordering contract documented on `get_locales` the same everywhere.
+pub struct LocaleList {
+    inner: Unique<provider::Locales>,
+}
+
+impl LocaleList {
+    pub(crate) fn new(inner: provider::Locales) -> Self {
+        Self {
+            inner: Unique::new(inner),
+        }
+    }
+
+    /// Resolves the preferred locales from `vars`, a snapshot of environment variables, with
+    /// the same algorithm [`get_locales`] uses for the current process on Unix.
+    ///
+    /// This lets tools resolve the locale of another process from its captured environment,
+    /// like the contents of `/proc/<pid>/environ`, or reproduce a user's resolution from the
+    /// variables they reported. `LANGUAGE`, `LC_ALL`, `LC_MESSAGES`, and `LANG` are read from
+    /// `vars` exactly as they would be from the environment. Only the environment is
+    /// considered: the fallbacks of the `kde` and `locale-conf` features, the
+    /// [locale hook](set_locale_hook), and mocked locales apply to the current process and
+    /// aren't used here.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use sys_locale::LocaleList;
+    ///
+    /// let mut vars = HashMap::new();
+    /// vars.insert(String::from("LANGUAGE"), String::from("fr_CA:fr"));
+    /// vars.insert(String::from("LANG"), String::from("en_US.UTF-8"));
+    ///
+    /// let locales: Vec<String> = LocaleList::from_env(&vars).collect();
+    /// assert_eq!(locales, ["fr-CA", "fr", "en-US"]);
+    /// ```
+    #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+    pub fn from_env(vars: &std::collections::HashMap<String, String>) -> LocaleList {
+        unix::from_env(vars)
+    }
+}
+
+impl Iterator for LocaleList {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Skips the locales of `I` that were already returned, keeping the first occurrence of each.
+///
+/// This is how [`LocaleList`] and the mocked locales skip repeats, so that both follow the same
+/// ordering contract.
+pub(crate) struct Unique<I> {
+    inner: I,
+    seen: alloc::vec::Vec<String>,
+}
+
+impl<I> Unique<I> {
+    pub(crate) fn new(inner: I) -> Self {
+        Self {
+            inner,
+            seen: alloc::vec::Vec::new(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = String>> Iterator for Unique<I> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let locale = self.inner.next()?;
+            if !self.seen.contains(&locale) {
+                self.seen.push(locale.clone());
+                return Some(locale);
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Anything after the first entry could be a repeat.
+        let (lower, upper) = self.inner.size_hint();
+        let lower = if self.seen.is_empty() {
+            lower.min(1)
+        } else {
+            0
+        };
+        (lower, upper)
     }
 }
 
 /// Returns the most preferred locale for the system or application.
 ///
-/// This is equivalent to `get_locales().next()` (the first entry).
+/// Without the `canonical` feature, this is equivalent to `get_locales().next()` (the first
+/// entry).
+///
+/// On Unix this describes the language messages should be displayed in, which is not
+/// necessarily the locale dates and numbers should be formatted with. Prefer
+/// [`get_message_locales`] or [`get_format_locale`] to make the intent explicit.
+///
+/// With the `canonical` feature, the tag is [canonicalized](canonicalize) like
+/// [`get_locale_canonical`] does. Without it, the tag is returned as the platform reports it.
+/// The feature is off by default so that the output doesn't change under existing users, and
+/// is planned to become the default in the next breaking release.
+///
+/// The feature only affects this function and those returning its result, like
+/// [`get_locale_result`], [`get_region`], and `get_locale_async`, as well as
+/// [`get_format_locale`] and [`get_category_locales`] on platforms without a separate format
+/// setting. Lists like [`get_locales`], [`get_message_locales`], [`locales`], and
+/// [`cached_locale`] keep the tags as reported, so a platform reporting `iw-il` gives `he-IL`
+/// here but `iw-il` as their first entry.
 ///
 /// # Returns
 ///
@@ -62,16 +421,203 @@ mod provider {
 /// println!("The locale is {}", current_locale);
 /// ```
 pub fn get_locale() -> Option<String> {
-    get_locales().next()
+    if cfg!(feature = "canonical") {
+        get_locale_canonical()
+    } else {
+        get_locales().next()
+    }
+}
+
+/// Returns the most preferred locale like [`get_locale`], or why none could be obtained.
+///
+/// Where [`get_locale`] returns [`None`] for every failure, this tells them apart: the target
+/// may not be [supported](SUPPORTED) at all, the platform may have no locale configured, or its
+/// API may have failed. On Windows, a failure of `GetUserPreferredUILanguages` is reported as
+/// [`LocaleError::ProviderError`] with the code from `GetLastError`, which helps diagnose
+/// machines where group policy restricts the API.
+///
+/// An application can use this to decide how to react: [`LocaleError::NotFound`] means the
+/// user could pick a language, while [`LocaleError::Unsupported`] means the platform will
+/// never report one, so falling back silently is the better choice.
+///
+/// # Example
+///
+/// ```no_run
+/// use sys_locale::LocaleError;
+///
+/// match sys_locale::get_locale_result() {
+///     Ok(locale) => println!("The locale is {}", locale),
+///     Err(LocaleError::NotFound) => println!("No locale is set, asking the user for one"),
+///     Err(error) => eprintln!("Using the default locale: {}", error),
+/// }
+/// ```
+pub fn get_locale_result() -> Result<String, LocaleError> {
+    if let Some(locale) = get_locale() {
+        return Ok(locale);
+    }
+    // Without a provider there was nothing to query, which is different from a platform that
+    // was queried but has no locale configured.
+    if !provider::SUPPORTED {
+        return Err(LocaleError::Unsupported);
+    }
+
+    // Failures are rare, so the API is only queried again to find out why once no locale was
+    // found, rather than threading errors through every provider.
+    #[cfg(windows)]
+    windows::read_languages().map_err(|code| LocaleError::ProviderError { code })?;
+
+    Err(LocaleError::NotFound)
+}
+
+/// Checks every preferred locale, returning either its [canonical](canonicalize) form or the
+/// entry as reported along with why it was rejected.
+///
+/// This is meant for tools that audit the locale configuration. Where [`primary_locale`]
+/// silently skips entries that aren't usable, this reports each of them, in the order of
+/// [`get_locales`], so a misconfigured variable or system setting can be found: an entry is
+/// rejected with [`LocaleError::Malformed`] if it isn't a well-formed tag, and with
+/// [`LocaleError::Undetermined`] if it doesn't name a language. [`explain`] shows where the
+/// entries come from.
+///
+/// # Example
+///
+/// ```no_run
+/// for entry in sys_locale::validate_locales() {
+///     match entry {
+///         Ok(tag) => println!("ok: {}", tag),
+///         Err((raw, error)) => println!("{:?}: {}", raw, error),
+///     }
+/// }
+/// ```
+pub fn validate_locales() -> alloc::vec::Vec<Result<String, (String, LocaleError)>> {
+    validate_from(get_locales())
+}
+
+fn validate_from(
+    locales: impl Iterator<Item = String>,
+) -> alloc::vec::Vec<Result<String, (String, LocaleError)>> {
+    locales
+        .map(|raw| match canonicalize(&raw) {
+            Some(tag) if Locale::parse(&tag).map_or(false, |l| l.is_undetermined()) => {
+                Err((raw, LocaleError::Undetermined))
+            }
+            Some(tag) => Ok(tag),
+            None => Err((raw, LocaleError::Malformed)),
+        })
+        .collect()
+}
+
+/// Returns the most preferred locale in its [canonical](canonicalize) form, regardless of the
+/// `canonical` feature.
+///
+/// Casing is normalized and deprecated subtags are replaced, so a platform reporting `en-us`
+/// or `iw-IL` gives `en-US` and `he-IL`. A tag that isn't well-formed, and so has no canonical
+/// form, is returned unchanged.
+///
+/// # Example
+///
+/// ```no_run
+/// let current_locale = sys_locale::get_locale_canonical();
+///
+/// println!("The locale is {:?}", current_locale);
+/// ```
+pub fn get_locale_canonical() -> Option<String> {
+    get_locales()
+        .next()
+        .map(|locale| locale::canonicalize_owned(locale).unwrap_or_else(|locale| locale))
+}
+
+/// Returns the locale set through the `SYS_LOCALE_DEFAULT` environment variable when this crate
+/// was built, if any.
+///
+/// This is meant for targets where the locale is fixed at build time, like firmware with a
+/// read-only configuration, which often have no platform API for [`get_locale`] to query. The
+/// tag is baked into the binary, so reading it costs nothing and allocates nothing. It isn't
+/// used by any other function, so it complements the runtime lookup rather than replacing it:
+///
+/// ```
+/// let locale = sys_locale::get_locale();
+/// let locale = locale.as_deref().or(sys_locale::const_locale()).unwrap_or("en-US");
+/// ```
+///
+/// The build fails if the variable isn't shaped like a BCP 47 tag, such as with a POSIX name
+/// like `en_US.UTF-8`. Changing the variable rebuilds the crate.
+pub const fn const_locale() -> Option<&'static str> {
+    option_env!("SYS_LOCALE_CONST_LOCALE")
+}
+
+/// The locale [`primary_locale`] returns when the system doesn't report a usable one.
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// Returns the most preferred locale that is a valid BCP 47 tag, or [`DEFAULT_LOCALE`] if there
+/// is none.
+///
+/// Unlike [`get_locale`], entries that aren't well-formed or that are
+/// [undetermined](Locale::is_undetermined) are skipped in favor of the next preference, and a
+/// tag is always returned. Use [`Resolver::default_locale`] to fall back to something other
+/// than `en-US`.
+///
+/// # Example
+///
+/// ```no_run
+/// use sys_locale::primary_locale;
+///
+/// println!("The locale is {}", primary_locale());
+/// ```
+pub fn primary_locale() -> String {
+    first_valid(get_locales()).unwrap_or_else(|| String::from(DEFAULT_LOCALE))
+}
+
+/// Returns the first tag in `locales` that is well-formed and names a language.
+fn first_valid(mut locales: impl Iterator<Item = String>) -> Option<String> {
+    locales.find(|tag| {
+        // Tags are usually canonical already, which can be checked without allocating.
+        let well_formed = is_canonical(tag) || Locale::parse(tag).is_some();
+        let language = tag.split('-').next().unwrap_or_default();
+        well_formed && !language.eq_ignore_ascii_case(Locale::UND)
+    })
 }
 
 /// Returns the preferred locales for the system or application, in descending order of preference.
 ///
+/// The system is queried again on every call. If the locale is treated as fixed after startup,
+/// [`locales`] avoids the repeated lookups.
+///
 /// # Returns
 ///
 /// Returns an [`Iterator`] with any number of BCP 47 language tags inside.  
 /// If no locale preferences could be obtained, the iterator will be empty.
 ///
+/// # Ordering
+///
+/// The first entry is always the most preferred locale, and every following entry is less
+/// preferred than the one before it. Every platform reports its preferences in this order, and
+/// the same tag is never returned twice. The first entry is the one returned by [`get_locale`].
+///
+/// Functions deriving their result from this list, like [`locales`], [`get_all_locales`], and
+/// [`get_locales_expanded`], keep the relative order of its entries.
+///
+/// Entries are never reordered by specificity. A platform can list a bare language before a
+/// more specific tag for it, such as `["en", "en-US"]`, which macOS does in some
+/// configurations, and the bare language then stays first. Use
+/// [`Resolver::prefer_specific`] to move the specific tag ahead, or [`maximize`] to fill in the
+/// likely region of a bare language.
+///
+/// # Apple platforms
+///
+/// The list comes from `CFLocaleCopyPreferredLanguages`, which is what
+/// `NSLocale.preferredLanguages` returns as well. Both read `AppleLanguages` through the
+/// preferences of the current application first, so a language picked for just this
+/// application, or passed with the `-AppleLanguages` launch argument, is already reflected.
+///
+/// # Android
+///
+/// The list comes from system properties and is the device's locale, so a language picked for
+/// just this application in the per-app language settings of Android 13 and later isn't
+/// reflected. Android applies that choice to the application's configuration instead, which
+/// `get_locale_from_configuration` reads. The full per-app list is only available from
+/// `LocaleManager.getApplicationLocales` through the JVM, which this crate doesn't attach to.
+///
 /// # Example
 ///
 /// ```no_run
@@ -83,17 +629,582 @@ pub fn get_locale() -> Option<String> {
 /// println!("The least preferred locale is {}", locales.last().unwrap_or("en-US".to_string()));
 /// ```
 pub fn get_locales() -> impl Iterator<Item = String> {
-    provider::get()
+    locales_from(provider::get)
+}
+
+/// Returns the locales from `provider` behind the one from the [locale hook](set_locale_hook), or
+/// the mocked ones when the `mock` feature sets any.
+pub(crate) fn locales_from(provider: impl FnOnce() -> LocaleList) -> impl Iterator<Item = String> {
+    #[cfg(not(feature = "mock"))]
+    {
+        hook::prepend(provider())
+    }
+    #[cfg(feature = "mock")]
+    {
+        mock::locales_or(|| hook::prepend(provider()))
+    }
+}
+
+/// Returns the preferred locales for translated messages, in descending order of preference.
+///
+/// On Unix this honors `LANGUAGE`, which GNU gettext uses to select message catalogs, ahead
+/// of `LC_ALL`, `LC_MESSAGES`, and `LANG`. Other platforms don't separate message and format
+/// preferences, so this is the same as [`get_locales`] there.
+///
+/// Use [`get_format_locale`] to decide how dates, times, and numbers should be formatted.
+pub fn get_message_locales() -> impl Iterator<Item = String> {
+    get_locales()
+}
+
+/// Returns the locale dates, times, numbers, and currency should be formatted with.
+///
+/// On Unix this ignores `LANGUAGE`, which only governs message translation, and uses the
+/// first value set out of `LC_ALL`, `LC_TIME`, `LC_NUMERIC`, `LC_MONETARY`, and `LANG`.
+/// A user with `LANGUAGE=en_US` and `LANG=de_DE.UTF-8` gets `de-DE` here, but `en-US` from
+/// [`get_message_locales`].
+///
+/// On Windows this is the "Regional format" setting (`GetUserDefaultLocaleName`), which can
+/// differ from the display languages. Other platforms return the same value as [`get_locale`].
+///
+/// # Returns
+///
+/// Returns [`Some(String)`] with a BCP 47 language tag inside.  
+/// If the locale couldn't be obtained, [`None`] is returned instead.
+pub fn get_format_locale() -> Option<String> {
+    #[cfg(feature = "mock")]
+    {
+        if let Some(mocked) = mock::format_locale() {
+            return mocked;
+        }
+    }
+
+    #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+    {
+        unix::get_format()
+    }
+    #[cfg(windows)]
+    {
+        windows::get_format()
+    }
+    #[cfg(not(any(
+        all(unix, not(any(target_vendor = "apple", target_os = "android"))),
+        windows
+    )))]
+    {
+        get_locale()
+    }
+}
+
+/// Returns the locale describing where the user lives, as opposed to the language they read.
+///
+/// On Unix this consults the categories tied to the user's region first, using the first value
+/// set out of `LC_ALL`, `LC_PAPER`, `LC_MEASUREMENT`, `LC_TIME`, and `LANG`. A user with
+/// `LANG=en_US.UTF-8` and `LC_PAPER=de_DE.UTF-8` gets `de-DE` here, even though their messages
+/// are in English. This is the Unix counterpart of the Windows regional format, so other
+/// platforms return the same value as [`get_format_locale`].
+///
+/// # Example
+///
+/// ```no_run
+/// let region = sys_locale::get_regional_locale()
+///     .and_then(|locale| sys_locale::Locale::parse(&locale))
+///     .and_then(|locale| locale.region().map(String::from));
+/// println!("The user lives in {:?}", region);
+/// ```
+pub fn get_regional_locale() -> Option<String> {
+    #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+    {
+        #[cfg(feature = "mock")]
+        {
+            if let Some(mocked) = mock::format_locale() {
+                return mocked;
+            }
+        }
+
+        unix::get_regional()
+    }
+    #[cfg(not(all(unix, not(any(target_vendor = "apple", target_os = "android")))))]
+    {
+        get_format_locale()
+    }
+}
+
+/// Returns the preferred locales followed by the format locale, without duplicates.
+///
+/// This is the list of [`get_locales`] with [`get_format_locale`] appended if it isn't
+/// already part of it, so that a user with an English interface and German regional formats
+/// gets `en-US, de-DE`. The format locale is always ordered last, since it describes how
+/// values should be formatted rather than which language the user wants to read.
+///
+/// Use [`get_locales`] for the pure list of display languages.
+///
+/// # Example
+///
+/// ```no_run
+/// use sys_locale::get_all_locales;
+///
+/// for locale in get_all_locales() {
+///     println!("Candidate locale: {}", locale);
+/// }
+/// ```
+pub fn get_all_locales() -> impl Iterator<Item = String> {
+    let mut locales: alloc::vec::Vec<String> = get_locales().collect();
+    if let Some(format) = get_format_locale() {
+        if !locales.contains(&format) {
+            locales.push(format);
+        }
+    }
+    locales.into_iter()
+}
+
+/// Returns the preferred locales with each one immediately followed by its fallbacks.
+///
+/// This is [`get_locales`] with every entry expanded by [`fallback_chain`], without
+/// duplicates: `["fr-CA", "en-US"]` becomes `["fr-CA", "fr", "en-US", "en"]`. When a tag is
+/// reached more than once, only its first, most preferred, occurrence is kept, so a language
+/// reached as a fallback won't reappear later in the list. Duplicates are detected without
+/// regard to case, and [undetermined](Locale::is_undetermined) entries like `und` are left
+/// out since they don't name a language to look for.
+///
+/// This is the list of candidates to try, in order, when loading resources for the user.
+///
+/// # Example
+///
+/// ```no_run
+/// use sys_locale::get_locales_expanded;
+///
+/// for candidate in get_locales_expanded() {
+///     println!("Trying translations for {}", candidate);
+/// }
+/// ```
+pub fn get_locales_expanded() -> impl Iterator<Item = String> {
+    expand_fallbacks(get_locales())
+}
+
+fn expand_fallbacks(locales: impl Iterator<Item = String>) -> impl Iterator<Item = String> {
+    let mut expanded: alloc::vec::Vec<String> = alloc::vec::Vec::new();
+    for locale in locales {
+        for candidate in fallback_chain(&locale) {
+            if !expanded.iter().any(|e| e.eq_ignore_ascii_case(&candidate)) {
+                expanded.push(candidate);
+            }
+        }
+    }
+    expanded.into_iter()
+}
+
+/// Returns the preferred locales as [RFC 4647](https://www.rfc-editor.org/rfc/rfc4647.html)
+/// basic language ranges, for matchers that take ranges rather than exact tags.
+///
+/// Each preference is followed by its truncated forms, so `["en-US", "fr"]` becomes
+/// `["en-US", "en", "fr"]`, and a matcher using lookup finds the generic `en` before moving
+/// on to `fr`. Entries that aren't [well-formed](is_valid_bcp47) are left out, since they
+/// aren't valid ranges, and so are [undetermined](Locale::is_undetermined) ones.
+///
+/// The three forms of the preferences serve different matchers:
+/// - [`get_locales`] returns the exact tags, for comparing against a list of supported tags
+///   with a matcher of your own, like [`negotiate`].
+/// - [`get_locales_expanded`] returns the same fallback candidates as this, but keeps
+///   malformed entries as reported, for trying resources by name in order.
+/// - This returns ranges, for RFC 4647 lookup or filtering, like `Accept-Language` handling.
+///
+/// RFC 4647 also defines the `*` range, which matches any tag. It isn't included, since it
+/// would make filtering return every tag, but it can be pushed to the end of the list to fall
+/// back to any available language instead of none:
+///
+/// ```no_run
+/// let mut ranges = sys_locale::locale_ranges();
+/// ranges.push(String::from("*"));
+/// ```
+pub fn locale_ranges() -> alloc::vec::Vec<String> {
+    ranges_from(get_locales())
+}
+
+fn ranges_from(locales: impl Iterator<Item = String>) -> alloc::vec::Vec<String> {
+    expand_fallbacks(locales)
+        .filter(|range| is_valid_bcp47(range))
+        .collect()
+}
+
+/// Returns the primary languages of the preferred locales, without duplicates, in descending
+/// order of preference.
+///
+/// This is for applications whose translations only vary by language: `["en-US", "en-GB",
+/// "fr-FR"]` becomes `["en", "fr"]`. Languages are lowercase, and each one is listed where it
+/// first appears. Entries that aren't well-formed, have no language, or are
+/// [undetermined](Locale::is_undetermined) are left out.
+///
+/// # Example
+///
+/// ```no_run
+/// let languages = sys_locale::get_languages();
+/// println!("The user reads {:?}", languages);
+/// ```
+pub fn get_languages() -> alloc::vec::Vec<String> {
+    unique_languages(get_locales())
+}
+
+fn unique_languages(locales: impl Iterator<Item = String>) -> alloc::vec::Vec<String> {
+    let mut languages: alloc::vec::Vec<String> = alloc::vec::Vec::new();
+    for locale in locales.filter_map(|l| Locale::parse(&l)) {
+        if locale.language().is_empty() || locale.is_undetermined() {
+            continue;
+        }
+        if !languages.iter().any(|l| l == locale.language()) {
+            languages.push(String::from(locale.language()));
+        }
+    }
+    languages
+}
+
+/// Returns a human-readable report of how the locales were resolved, for troubleshooting.
+///
+/// The report lists every source the platform provider consulted with the raw value it found,
+/// such as each environment variable on Unix or the output of the system API elsewhere, along
+/// with how the values were converted. It ends with the final results of [`get_locales`] and
+/// [`get_format_locale`]. Attaching it to a bug report shows exactly what the crate saw.
+///
+/// This reads the same sources as [`get_locales`] and doesn't change any process state. The
+/// format of the report isn't stable and may change in any release.
+///
+/// # Example
+///
+/// ```no_run
+/// eprintln!("{}", sys_locale::explain());
+/// ```
+pub fn explain() -> String {
+    use core::fmt::Write;
+
+    fn write_report(report: &mut String) -> core::fmt::Result {
+        writeln!(report, "sys-locale {}", env!("CARGO_PKG_VERSION"))?;
+        provider::explain(report)?;
+        let locales: alloc::vec::Vec<String> = get_locales().collect();
+        writeln!(report, "Result:")?;
+        writeln!(report, "  get_locales() = {:?}", locales)?;
+        writeln!(report, "  get_format_locale() = {:?}", get_format_locale())
+    }
+
+    let mut report = String::new();
+    // Writing to a `String` can't fail.
+    let _ = write_report(&mut report);
+    report
+}
+
+/// Returns the region of the most preferred locale, such as `US` or `419`.
+///
+/// This is derived from the language tag returned by [`get_locale`], so it's [`None`] when
+/// that tag has no region (like `en`) or isn't well-formed. Use [`get_country_code`] for the
+/// country the user has configured on the system, which can be different.
+///
+/// # Example
+///
+/// ```no_run
+/// use sys_locale::get_region;
+///
+/// println!("The preferred region is {}", get_region().unwrap_or_else(|| String::from("US")));
+/// ```
+pub fn get_region() -> Option<String> {
+    let locale = Locale::parse(&get_locale()?)?;
+    locale.region().map(String::from)
+}
+
+/// Returns the region of the most preferred locale as a validated [`Region`].
+///
+/// This is the typed counterpart of [`get_region`], and returns [`None`] in the same cases.
+///
+/// # Example
+///
+/// ```no_run
+/// use sys_locale::get_region_typed;
+///
+/// if let Some(region) = get_region_typed() {
+///     if region == "US" {
+///         println!("Using imperial units");
+///     }
+/// }
+/// ```
+pub fn get_region_typed() -> Option<Region> {
+    Region::new(&get_region()?)
+}
+
+/// Returns the country the user has configured, as an ISO 3166-1 alpha-2 code such as `JP`.
+///
+/// On Apple platforms this is the explicit "Region" setting (`kCFLocaleCountryCode`), which
+/// is independent from the preferred languages: a user reading English in Japan gets `en-US`
+/// from [`get_locale`] but `JP` here. Elsewhere it's taken from the region of
+/// [`get_format_locale`], which is the closest equivalent the platforms provide.
+///
+/// Returns [`None`] if no country is configured or the region isn't a two letter code.
+pub fn get_country_code() -> Option<String> {
+    #[cfg(target_vendor = "apple")]
+    let country = apple::get_country_code();
+    #[cfg(not(target_vendor = "apple"))]
+    let country = Locale::parse(&get_format_locale()?)?
+        .region()
+        .map(String::from);
+
+    country.filter(|c| c.len() == 2 && c.bytes().all(|b| b.is_ascii_alphabetic()))
+}
+
+/// Returns the IANA id of the system time zone, such as `Europe/Paris`.
+///
+/// This is a best-effort companion to the locale functions, for applications that need both:
+/// - On Apple platforms, it's the name of `CFTimeZoneCopySystem`.
+/// - On Android, it's the `persist.sys.timezone` system property.
+/// - On Windows, the current time zone's key name is mapped to an IANA id through the CLDR
+///   `windowsZones` table. Zones missing from the table, like custom ones, return [`None`].
+/// - On other Unix platforms, it's read from `TZ` when that holds an id or a path into the
+///   time zone database, then from `/etc/timezone`, then from the target of the
+///   `/etc/localtime` link.
+///
+/// Ids aren't validated against the time zone database, and may be older aliases of the
+/// current canonical ids, such as `Asia/Calcutta` for `Asia/Kolkata`. [`None`] is returned if
+/// the time zone couldn't be determined, and always on other platforms.
+///
+/// # Example
+///
+/// ```no_run
+/// use sys_locale::get_timezone;
+///
+/// let timezone = get_timezone().unwrap_or_else(|| String::from("UTC"));
+/// println!("The time zone is {}", timezone);
+/// ```
+pub fn get_timezone() -> Option<String> {
+    #[cfg(target_vendor = "apple")]
+    {
+        apple::get_timezone()
+    }
+    #[cfg(target_os = "android")]
+    {
+        android::get_timezone()
+    }
+    #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+    {
+        unix::get_timezone()
+    }
+    #[cfg(windows)]
+    {
+        windows::get_timezone()
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        None
+    }
+}
+
+/// Returns the name of the locale `tag` for showing to users, such as `Deutsch (Schweiz)` for
+/// `de-CH`, in the language of `in_locale`.
+///
+/// Settings screens listing the available languages usually show each in its own language,
+/// with `in_locale` set to `tag`, or in the current UI language, with `in_locale` set to
+/// [`None`]. The names come from the platform, so this doesn't need any data of its own:
+/// - On Apple platforms, any locale can be named in any language, through
+///   `CFLocaleCopyDisplayNameForPropertyValue`.
+/// - On Windows, `GetLocaleInfoEx` can only give the name in the language of the Windows UI,
+///   for [`None`], or in the locale's own language, when `in_locale` is `tag`. Other languages
+///   return [`None`].
+///
+/// Every other platform returns [`None`], as do tags the platform doesn't know.
+///
+/// # Example
+///
+/// ```no_run
+/// for tag in ["en-US", "fr-CA", "ja"] {
+///     let name = sys_locale::display_name(tag, Some(tag)).unwrap_or_else(|| String::from(tag));
+///     println!("{}", name);
+/// }
+/// ```
+pub fn display_name(tag: &str, in_locale: Option<&str>) -> Option<String> {
+    #[cfg(target_vendor = "apple")]
+    {
+        apple::display_name(tag, in_locale)
+    }
+    #[cfg(windows)]
+    {
+        windows::display_name(tag, in_locale)
+    }
+    #[cfg(not(any(target_vendor = "apple", windows)))]
+    {
+        let _ = (tag, in_locale);
+        None
+    }
+}
+
+/// Returns the description glibc keeps about the current locale in its `LC_IDENTIFICATION`
+/// category, such as its title and the names of its language and territory.
+///
+/// This is meant for tools that inspect the locale configuration, and isn't needed to use the
+/// locale. The locale is the one `setlocale` would pick for `LC_IDENTIFICATION`, from
+/// `LC_ALL`, `LC_IDENTIFICATION`, or `LANG`, which can differ from [`get_locale`] since
+/// `LANGUAGE` isn't considered. It's loaded on its own, without changing the locale of the
+/// process.
+///
+/// Returns [`None`] if that locale isn't installed, or with C libraries other than glibc,
+/// which don't provide this category.
+///
+/// # Example
+///
+/// ```no_run
+/// if let Some(metadata) = sys_locale::get_locale_metadata() {
+///     println!("The locale is {:?}", metadata.title);
+/// }
+/// ```
+#[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+pub fn get_locale_metadata() -> Option<LocaleMetadata> {
+    metadata::read("")
+}
+
+/// Returns the UI language the calling thread has set for itself on Windows.
+///
+/// Services that handle several users often give each thread the language of the user it's
+/// working for with `SetThreadPreferredUILanguages`. This reads that setting back with
+/// `GetThreadPreferredUILanguages`, returning the thread's first language. Unlike
+/// [`get_locale`], which reports the preferences of the signed in user, this returns [`None`]
+/// if the thread hasn't set any languages of its own, rather than falling back to the process,
+/// user, or system languages.
+///
+/// # Example
+///
+/// ```no_run
+/// match sys_locale::get_thread_locale() {
+///     Some(locale) => println!("This thread uses {}", locale),
+///     None => println!("This thread uses the user's languages"),
+/// }
+/// ```
+#[cfg(windows)]
+pub fn get_thread_locale() -> Option<String> {
+    windows::get_thread().next()
+}
+
+/// Returns the user's home location on Windows, as an ISO 3166-1 alpha-2 code such as `US`.
+///
+/// This is the "Country or region" in the Windows region settings, read with
+/// `GetUserDefaultGeoName`. It's where the user says they are, for things like store fronts or
+/// regulatory requirements, and is set independently from both the UI language of
+/// [`get_locale`] and the regional format of [`get_format_locale`]: someone in Germany may use
+/// an English UI with French formats, and get `DE` here.
+///
+/// Returns [`None`] if no location is set, the location isn't a country with a two letter code,
+/// or the API isn't available, which is the case before Windows 10 version 1709.
+///
+/// # Example
+///
+/// ```no_run
+/// if sys_locale::get_geo_region().as_deref() == Some("DE") {
+///     println!("Showing the German store front");
+/// }
+/// ```
+#[cfg(windows)]
+pub fn get_geo_region() -> Option<String> {
+    windows::get_geo_name()
+        .filter(|name| name.len() == 2 && name.bytes().all(|b| b.is_ascii_alphabetic()))
+}
+
+/// Returns the legacy Windows locale identifier (LCID) of the most preferred locale.
+///
+/// This is intended for bridging to older Windows components that only accept numeric
+/// LCIDs. Locales that don't have a dedicated LCID, which includes most locales added since
+/// Windows Vista, return [`None`].
+#[cfg(windows)]
+pub fn get_lcid() -> Option<u32> {
+    windows::locale_name_to_lcid(&get_locale()?)
+}
+
+/// Converts a BCP 47 language tag into a legacy Windows locale identifier (LCID).
+///
+/// Returns [`None`] if Windows doesn't know the locale or it has no dedicated LCID.
+#[cfg(windows)]
+pub fn bcp47_to_lcid(tag: &str) -> Option<u32> {
+    windows::locale_name_to_lcid(tag)
+}
+
+/// Converts a legacy Windows locale identifier (LCID) into a BCP 47 language tag.
+///
+/// For example, `0x0409` becomes `en-US`. Returns [`None`] if Windows doesn't recognize
+/// `lcid`.
+#[cfg(windows)]
+pub fn lcid_to_bcp47(lcid: u32) -> Option<String> {
+    windows::lcid_to_locale_name(lcid)
+}
+
+/// Returns the most preferred locale of another application on macOS, given its bundle
+/// identifier, such as `com.apple.Safari`.
+///
+/// This reads the application's `AppleLanguages` preference, which is what
+/// `defaults read <bundle_id> AppleLanguages` shows and what's set when the user picks a
+/// language for a single application in System Settings. Applications without their own
+/// setting use the system's preferred languages, so those are returned instead. This is meant
+/// for tools like launchers or diagnostic utilities, since [`get_locale`] already accounts for
+/// the current application's setting.
+///
+/// Returns [`None`] if no preferred language is set, or if the preferences can't be read, which
+/// can happen from within the App Sandbox.
+///
+/// # Example
+///
+/// ```no_run
+/// let locale = sys_locale::get_locale_for_bundle("com.apple.Safari");
+/// println!("Safari is shown in {:?}", locale);
+/// ```
+#[cfg(target_os = "macos")]
+pub fn get_locale_for_bundle(bundle_id: &str) -> Option<String> {
+    apple::get_locale_for_bundle(bundle_id)
+}
+
+/// Returns the locale of an NDK `AConfiguration`, for native applications that have one but
+/// don't want to attach to the JVM.
+///
+/// [`get_locale`] reads the system properties, which describe the device's locale. The
+/// configuration of an application instead reflects what its resources are resolved with, and
+/// is how pure native engines usually learn about the locale. Only the language and country
+/// are available through the NDK, so the tag never has a script or a variant.
+///
+/// On Android 13 and later, this includes the language picked for the application in the
+/// per-app language settings, if any, since Android applies it to the application's
+/// configuration. Only the first of the per-app locales is available this way.
+///
+/// The caller supplies the configuration. With `native_app_glue`, it's the `config` field of
+/// the `android_app`. Otherwise, one can be filled in from an `AAssetManager` with
+/// `AConfiguration_new` and `AConfiguration_fromAssetManager`. Pointers from other bindings,
+/// like the `ndk` crate's `Configuration::ptr`, can be passed by casting them.
+///
+/// Returns [`None`] if the configuration has no language.
+///
+/// # Safety
+///
+/// `config` must point to a valid `AConfiguration` for the duration of the call.
+///
+/// # Example
+///
+/// ```ignore
+/// // `app` is the `android_app` passed to `android_main` by `native_app_glue`.
+/// let locale = unsafe { sys_locale::get_locale_from_configuration((*app).config.cast()) };
+/// ```
+#[cfg(target_os = "android")]
+pub unsafe fn get_locale_from_configuration(config: *mut core::ffi::c_void) -> Option<String> {
+    android::get_from_configuration(config)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{get_locale, get_locales};
+    use super::{
+        expand_fallbacks, first_valid, get_locale, get_locales, ranges_from, unique_languages,
+        validate_from, LocaleError,
+    };
+    use alloc::{string::String, vec::Vec};
     extern crate std;
 
-    #[cfg(all(target_family = "wasm", feature = "js", not(unix)))]
+    #[cfg(all(
+        target_family = "wasm",
+        any(feature = "js", feature = "js-minimal"),
+        not(unix)
+    ))]
     use wasm_bindgen_test::wasm_bindgen_test as test;
-    #[cfg(all(target_family = "wasm", feature = "js", not(unix)))]
+    #[cfg(all(
+        target_family = "wasm",
+        any(feature = "js", feature = "js-minimal"),
+        not(unix)
+    ))]
     wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
 
     #[test]
@@ -109,4 +1220,98 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn expands_fallbacks() {
+        let expand = |locales: &[&str]| {
+            expand_fallbacks(locales.iter().map(|l| String::from(*l))).collect::<Vec<_>>()
+        };
+
+        assert_eq!(expand(&["fr-CA", "en-US"]), ["fr-CA", "fr", "en-US", "en"]);
+        assert_eq!(expand(&["en-US", "en-GB", "en"]), ["en-US", "en", "en-GB"]);
+        assert_eq!(expand(&["en", "en-US"]), ["en", "en-US"]);
+        assert_eq!(expand(&["de-DE", "DE"]), ["de-DE", "de"]);
+        assert_eq!(expand(&["und", "fr-FR"]), ["fr-FR", "fr"]);
+    }
+
+    #[test]
+    fn collapses_languages() {
+        let languages =
+            |locales: &[&str]| unique_languages(locales.iter().map(|l| String::from(*l)));
+
+        assert_eq!(languages(&["en-US", "en-GB", "fr-FR"]), ["en", "fr"]);
+        assert_eq!(languages(&["FR-ca", "de", "fr"]), ["fr", "de"]);
+        assert_eq!(
+            languages(&["und-US", "x-klingon", "en_US", "zh-Hant-TW"]),
+            ["zh"]
+        );
+        assert!(languages(&[]).is_empty());
+    }
+
+    #[test]
+    fn validates() {
+        let locales = ["en-us", "en_US", "und-US", "iw", "en--US", "x-private"];
+        assert_eq!(
+            validate_from(locales.iter().map(|l| String::from(*l))),
+            [
+                Ok(String::from("en-US")),
+                Err((String::from("en_US"), LocaleError::Malformed)),
+                Err((String::from("und-US"), LocaleError::Undetermined)),
+                Ok(String::from("he")),
+                Err((String::from("en--US"), LocaleError::Malformed)),
+                Ok(String::from("x-private")),
+            ]
+        );
+    }
+
+    #[test]
+    fn converts_to_ranges() {
+        let ranges = |locales: &[&str]| ranges_from(locales.iter().map(|l| String::from(*l)));
+
+        assert_eq!(ranges(&["en-US", "fr"]), ["en-US", "en", "fr"]);
+        assert_eq!(
+            ranges(&["zh-Hant-TW", "en_US", "und", "zh-Hans"]),
+            ["zh-Hant-TW", "zh-Hant", "zh", "zh-Hans"]
+        );
+        assert_eq!(ranges(&["de-CH-x-corp"]), ["de-CH-x-corp", "de-CH", "de"]);
+        assert!(ranges(&[]).is_empty());
+    }
+
+    #[test]
+    fn finds_first_valid() {
+        let first = |locales: &[&str]| first_valid(locales.iter().map(|l| String::from(*l)));
+
+        assert_eq!(first(&["fr-CA", "en-US"]).as_deref(), Some("fr-CA"));
+        assert_eq!(first(&["en_US", "und", "-US", "de"]).as_deref(), Some("de"));
+        assert_eq!(first(&["und-US", "UND", ""]), None);
+        assert_eq!(first(&["x-", "x-klingon"]).as_deref(), Some("x-klingon"));
+        assert_eq!(
+            first(&["en-x-", "en-US-x-private"]).as_deref(),
+            Some("en-US-x-private")
+        );
+        assert_eq!(first(&[]), None);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn converts_lcids() {
+        use super::{bcp47_to_lcid, lcid_to_bcp47};
+
+        assert_eq!(lcid_to_bcp47(0x0409).as_deref(), Some("en-US"));
+        assert_eq!(lcid_to_bcp47(0x0407).as_deref(), Some("de-DE"));
+        assert_eq!(bcp47_to_lcid("en-US"), Some(0x0409));
+        assert_eq!(bcp47_to_lcid("not-a-locale"), None);
+    }
+
+    #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+    #[test]
+    fn uses_environment_on_illumos() {
+        assert!(super::explain().contains("Provider: environment variables"));
+    }
+
+    #[test]
+    fn host_is_supported() {
+        // Tests only run on targets with a real provider.
+        assert!(super::SUPPORTED);
+    }
 }
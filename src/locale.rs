@@ -0,0 +1,2013 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{Language, Region};
+
+/// The role a subtag plays inside of a BCP 47 language tag.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SubtagKind {
+    /// The primary language subtag, such as `en` or `zh`.
+    Language,
+    /// The four letter script subtag, such as `Latn` or `Hans`.
+    Script,
+    /// The region subtag, either two letters (`US`) or three digits (`419`).
+    Region,
+    /// A registered variant subtag, such as `valencia` or `1996`.
+    Variant,
+    /// A subtag of an extension sequence, such as `u-ca-japanese`, including its singleton.
+    Extension,
+    /// A subtag of the private use sequence, such as `x-custom`, including the `x` singleton.
+    PrivateUse,
+}
+
+/// A well-formed BCP 47 language tag, split into its subtags.
+///
+/// Subtags are stored in their canonical case: the language, variants, extensions, and
+/// private use sequence are lowercase, the script is titlecase, and the region is uppercase.
+/// This means that two tags differing only by case compare equal.
+///
+/// Locales are ordered lexicographically by their subtags: the language, then the script, the
+/// region, the variants, the extensions, and the private use sequence, where a missing subtag
+/// comes first. So `en` sorts before `en-US`, which sorts before `en-US-x-foo`. This gives a
+/// stable order for displaying or comparing lists of locales, but has nothing to do with the
+/// user's preference, which is the order of [`get_locales`](crate::get_locales).
+///
+/// # Example
+///
+/// ```
+/// use sys_locale::{Locale, SubtagKind};
+///
+/// let locale = Locale::parse("zh-hant-tw").unwrap();
+/// assert_eq!(locale.language(), "zh");
+/// assert_eq!(locale.script(), Some("Hant"));
+/// assert_eq!(locale.region(), Some("TW"));
+/// assert_eq!(locale.to_string(), "zh-Hant-TW");
+/// assert_eq!(locale.subtag(1), Some((SubtagKind::Script, "Hant")));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Locale {
+    language: String,
+    script: Option<String>,
+    region: Option<String>,
+    variants: Vec<String>,
+    /// Each extension sequence, including its singleton (`u-ca-japanese`).
+    extensions: Vec<String>,
+    /// The private use sequence, including the `x` singleton (`x-custom`).
+    private_use: Option<String>,
+}
+
+impl Locale {
+    /// The language subtag BCP 47 reserves for an undetermined language.
+    ///
+    /// Some minimal environments report this instead of a real language, which usually
+    /// should be treated the same as no preference at all.
+    pub const UND: &'static str = "und";
+
+    /// Creates a locale from a language and a region subtag, such as `en` and `US`.
+    ///
+    /// Returns [`None`] if either subtag isn't valid. See [`Locale::builder`] for tags with
+    /// more subtags.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sys_locale::Locale;
+    ///
+    /// assert_eq!(Locale::new("en", "us").unwrap().to_string(), "en-US");
+    /// assert_eq!(Locale::new("en", "USA"), None);
+    /// ```
+    pub fn new(language: &str, region: &str) -> Option<Locale> {
+        Locale::builder().language(language).region(region).build()
+    }
+
+    /// Returns a [`LocaleBuilder`] for creating a locale subtag by subtag.
+    pub fn builder() -> LocaleBuilder {
+        LocaleBuilder::default()
+    }
+
+    /// Parses a BCP 47 language tag.
+    ///
+    /// Subtags must be separated by `-` and follow the
+    /// [RFC 5646](https://www.rfc-editor.org/rfc/rfc5646.html#section-2.1) syntax, in any case.
+    /// Tags consisting of only a private use sequence, like `x-klingon`, are accepted and have
+    /// an empty [`language`](Self::language).
+    ///
+    /// Returns [`None`] if `tag` isn't well-formed.
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let mut locale = Locale {
+            language: String::new(),
+            script: None,
+            region: None,
+            variants: Vec::new(),
+            extensions: Vec::new(),
+            private_use: None,
+        };
+
+        let well_formed = classify(tag, |kind, subtag| {
+            let subtag = subtag.to_ascii_lowercase();
+            match kind {
+                SubtagKind::Language => locale.language = subtag,
+                SubtagKind::Script => locale.script = Some(titlecase(&subtag)),
+                SubtagKind::Region => locale.region = Some(subtag.to_ascii_uppercase()),
+                SubtagKind::Variant => locale.variants.push(subtag),
+                SubtagKind::Extension if subtag.len() == 1 => locale.extensions.push(subtag),
+                SubtagKind::Extension => append(locale.extensions.last_mut(), &subtag),
+                SubtagKind::PrivateUse => match &mut locale.private_use {
+                    Some(sequence) => append(Some(sequence), &subtag),
+                    None => locale.private_use = Some(subtag),
+                },
+            }
+        });
+
+        Some(locale).filter(|_| well_formed)
+    }
+
+    /// Parses a tag from messy input, like user input or legacy configuration, salvaging what
+    /// [`parse`](Self::parse) would reject outright.
+    ///
+    /// Before parsing:
+    /// - Whitespace around the tag is removed.
+    /// - `_` is accepted as a separator, like in `en_US`.
+    /// - Empty subtags are dropped, so leading, trailing, and doubled separators like the ones
+    ///   in `en-US-`, `-en-US`, or `en--US` are ignored.
+    ///
+    /// The result is then [canonicalized](Self::canonical), with the whole tag aliases handled
+    /// by [`canonicalize`] applied too. Subtags that are invalid in themselves, like the `USA`
+    /// in `en-USA`, aren't dropped or repaired, so such tags still return [`None`]. Codesets
+    /// and modifiers of POSIX locale names aren't understood either; use
+    /// [`posix_to_bcp47`](crate::posix_to_bcp47) for those.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sys_locale::Locale;
+    ///
+    /// assert_eq!(Locale::parse("en--us"), None);
+    /// assert_eq!(Locale::parse_lossy("en--us").unwrap().to_string(), "en-US");
+    /// assert_eq!(Locale::parse_lossy(" iw_IL- ").unwrap().to_string(), "he-IL");
+    /// ```
+    pub fn parse_lossy(tag: &str) -> Option<Locale> {
+        let subtags: Vec<&str> = tag
+            .trim()
+            .split(|c| c == '-' || c == '_')
+            .filter(|subtag| !subtag.is_empty())
+            .collect();
+        Locale::parse(&canonicalize(&subtags.join("-"))?)
+    }
+
+    /// Returns the primary language subtag, such as `en`.
+    ///
+    /// This is empty for tags consisting of only a private use sequence.
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Returns the primary language subtag as a validated [`Language`].
+    ///
+    /// This is [`None`] for tags consisting of only a private use sequence.
+    pub fn language_typed(&self) -> Option<Language> {
+        Language::new(&self.language)
+    }
+
+    /// Returns the script subtag, such as `Latn`, if there is one.
+    pub fn script(&self) -> Option<&str> {
+        self.script.as_deref()
+    }
+
+    /// Returns the script subtag, or the script the language is most likely written in if there
+    /// is none, such as `Hans` for `zh`.
+    ///
+    /// This is what choosing fonts or glyph variants needs, where a tag like `zh` or `sr` alone
+    /// doesn't say which script to use. The likely script comes from the same CLDR data as
+    /// [`maximize`](Self::maximize), so it's a heuristic that depends on the region as well:
+    /// `zh-CN` gives `Hans` while `zh-TW` gives `Hant`, and `sr` gives `Cyrl` while `sr-ME` gives
+    /// `Latn`. Returns [`None`] for languages outside of that data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sys_locale::Locale;
+    ///
+    /// assert_eq!(Locale::parse("zh-TW").unwrap().script_or_likely().as_deref(), Some("Hant"));
+    /// assert_eq!(Locale::parse("sr-Latn").unwrap().script_or_likely().as_deref(), Some("Latn"));
+    /// ```
+    pub fn script_or_likely(&self) -> Option<String> {
+        match &self.script {
+            Some(script) => Some(script.clone()),
+            None => self.maximize().script,
+        }
+    }
+
+    /// Returns the region subtag, such as `US` or `419`, if there is one.
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    /// Returns the region subtag as a validated [`Region`], if there is one.
+    pub fn region_typed(&self) -> Option<Region> {
+        Region::new(self.region.as_deref()?)
+    }
+
+    /// Returns only the language and region, such as `zh-CN` for `zh-Hans-CN-u-nu-latn`, or just
+    /// the language when there's no region.
+    ///
+    /// This is lossy: the script, variants, extensions, and private use sequence are dropped,
+    /// so `zh-Hant-CN` and `zh-Hans-CN` give the same result. It's meant as a coarse key, like
+    /// for looking up a table of formatting rules, and not as a tag to hand back to the user.
+    /// For tags consisting of only a private use sequence, this is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sys_locale::Locale;
+    ///
+    /// let locale = Locale::parse("zh-Hans-CN-u-nu-latn").unwrap();
+    /// assert_eq!(locale.language_region(), "zh-CN");
+    /// ```
+    pub fn language_region(&self) -> String {
+        let mut key = self.language.clone();
+        if let Some(region) = &self.region {
+            key.push('-');
+            key.push_str(region);
+        }
+        key
+    }
+
+    /// Returns this locale with its script subtag added or replaced by `script`.
+    ///
+    /// The script is stored in canonical case, so `zh` with `hant` becomes `zh-Hant`. Returns
+    /// [`None`] if `script` isn't a four letter script subtag, or if the locale has no language
+    /// to attach it to.
+    pub fn with_script(&self, script: &str) -> Option<Locale> {
+        if !is_script(script) || self.language.is_empty() {
+            return None;
+        }
+
+        let mut locale = self.clone();
+        locale.script = Some(titlecase(script));
+        Some(locale)
+    }
+
+    /// Returns this locale with its region subtag added or replaced by `region`.
+    ///
+    /// The region is stored in canonical case, so `en` with `gb` becomes `en-GB`. Returns
+    /// [`None`] if `region` isn't a two letter or three digit region subtag, or if the locale
+    /// has no language to attach it to.
+    pub fn with_region(&self, region: &str) -> Option<Locale> {
+        if !is_region(region) || self.language.is_empty() {
+            return None;
+        }
+
+        let mut locale = self.clone();
+        locale.region = Some(region.to_ascii_uppercase());
+        Some(locale)
+    }
+
+    /// Returns the variant subtags in the order they appear.
+    pub fn variants(&self) -> impl Iterator<Item = &str> {
+        self.variants.iter().map(String::as_str)
+    }
+
+    /// Returns the subtags of the extension introduced by `singleton`, without the singleton.
+    ///
+    /// For `de-DE-u-co-phonebk-t-en`, `extension('u')` is `co-phonebk` and `extension('t')` is
+    /// `en`. The singleton is matched without regard to case. The `x` private use sequence isn't
+    /// an extension, so it's never returned.
+    pub fn extension(&self, singleton: char) -> Option<&str> {
+        let singleton = singleton.to_ascii_lowercase();
+        self.extensions
+            .iter()
+            .find(|e| e.starts_with(singleton))
+            .map(|e| &e[2..])
+    }
+
+    /// Returns the value of a Unicode locale extension (`-u-`) keyword.
+    ///
+    /// Keywords configure preferences like the calendar (`ca`), numbering system (`nu`), or hour
+    /// cycle (`hc`). For `ja-JP-u-ca-japanese-hc-h23`, `unicode_extension("ca")` is `japanese`.
+    /// A keyword with multiple type subtags returns all of them, separated by `-`, and a keyword
+    /// without any returns an empty string, which means `true`.
+    ///
+    /// Returns [`None`] if the keyword isn't present.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sys_locale::Locale;
+    ///
+    /// let locale = Locale::parse("ja-JP-u-ca-japanese-nu-latn").unwrap();
+    /// assert_eq!(locale.unicode_extension("ca"), Some("japanese"));
+    /// assert_eq!(locale.unicode_extension("nu"), Some("latn"));
+    /// assert_eq!(locale.unicode_extension("hc"), None);
+    /// ```
+    pub fn unicode_extension(&self, key: &str) -> Option<&str> {
+        let extension = self.extension('u')?;
+
+        // Keys are the only two character subtags, while attributes and types are longer.
+        let mut start = None;
+        let mut offset = 0;
+        for subtag in extension.split('-') {
+            let end = offset + subtag.len();
+            if subtag.len() == 2 {
+                if let Some(start) = start {
+                    // The value ends before the separator preceding the next key.
+                    return Some(&extension[start..offset.saturating_sub(1).max(start)]);
+                }
+                if subtag.eq_ignore_ascii_case(key) {
+                    start = Some((end + 1).min(extension.len()));
+                }
+            }
+            offset = end + 1;
+        }
+
+        start.map(|start| &extension[start..])
+    }
+
+    /// Returns the subtags of the private use sequence, without the `x` singleton.
+    ///
+    /// For `en-US-x-custom-build`, this is `custom-build`. Private use subtags have no meaning
+    /// outside of an agreement between the parties exchanging the tag.
+    pub fn private_use(&self) -> Option<&str> {
+        self.private_use.as_deref().map(|p| &p[2..])
+    }
+
+    /// Removes the private use sequence, returning `true` if there was one.
+    ///
+    /// Extensions that precede it are kept.
+    pub(crate) fn strip_private_use(&mut self) -> bool {
+        self.private_use.take().is_some()
+    }
+
+    /// Returns every subtag of the tag in order, along with the role it plays.
+    ///
+    /// Extension and private use sequences yield their singleton (`u`, `t`, `x`, ...) followed
+    /// by each of their subtags, all tagged as [`SubtagKind::Extension`] or
+    /// [`SubtagKind::PrivateUse`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sys_locale::{Locale, SubtagKind};
+    ///
+    /// let locale = Locale::parse("ja-JP-u-ca-japanese-x-custom").unwrap();
+    /// let subtags: Vec<_> = locale.subtags().collect();
+    /// assert_eq!(
+    ///     subtags,
+    ///     [
+    ///         (SubtagKind::Language, "ja"),
+    ///         (SubtagKind::Region, "JP"),
+    ///         (SubtagKind::Extension, "u"),
+    ///         (SubtagKind::Extension, "ca"),
+    ///         (SubtagKind::Extension, "japanese"),
+    ///         (SubtagKind::PrivateUse, "x"),
+    ///         (SubtagKind::PrivateUse, "custom"),
+    ///     ]
+    /// );
+    /// ```
+    pub fn subtags(&self) -> Subtags<'_> {
+        let mut subtags = Vec::new();
+        if !self.language.is_empty() {
+            subtags.push((SubtagKind::Language, self.language.as_str()));
+        }
+        if let Some(script) = &self.script {
+            subtags.push((SubtagKind::Script, script.as_str()));
+        }
+        if let Some(region) = &self.region {
+            subtags.push((SubtagKind::Region, region.as_str()));
+        }
+        for variant in &self.variants {
+            subtags.push((SubtagKind::Variant, variant.as_str()));
+        }
+        for extension in &self.extensions {
+            subtags.extend(extension.split('-').map(|s| (SubtagKind::Extension, s)));
+        }
+        if let Some(private_use) = &self.private_use {
+            subtags.extend(private_use.split('-').map(|s| (SubtagKind::PrivateUse, s)));
+        }
+
+        Subtags {
+            inner: subtags.into_iter(),
+        }
+    }
+
+    /// Returns the subtag at `index`, counting from the primary language subtag.
+    ///
+    /// This indexes the same sequence produced by [`subtags`](Self::subtags).
+    pub fn subtag(&self, index: usize) -> Option<(SubtagKind, &str)> {
+        self.subtags().nth(index)
+    }
+
+    /// Returns an iterator over this locale followed by progressively less specific forms of it.
+    ///
+    /// This follows the [RFC 4647](https://www.rfc-editor.org/rfc/rfc4647.html#section-3.4)
+    /// lookup truncation, removing one subtag (or one whole extension or private use sequence)
+    /// from the end at a time: `zh-Hant-TW` yields `zh-Hant-TW`, `zh-Hant`, and `zh`. The
+    /// iterator ends with the bare language subtag.
+    ///
+    /// An [undetermined](Self::is_undetermined) locale doesn't describe any language, so it
+    /// yields nothing.
+    pub fn fallbacks(&self) -> Fallbacks {
+        Fallbacks {
+            next: Some(self.clone()).filter(|locale| !locale.is_undetermined()),
+        }
+    }
+
+    /// Returns `true` if the language is [`und`](Self::UND), such as in `und` or `und-US`.
+    ///
+    /// The region or script of such a tag may still be meaningful, but it doesn't state which
+    /// language the user wants.
+    pub fn is_undetermined(&self) -> bool {
+        self.language == Self::UND
+    }
+
+    /// Returns this locale with deprecated subtags replaced by their preferred values.
+    ///
+    /// Language subtags that IANA replaced, like `iw` for Hebrew, become their successor
+    /// (`he`), and region aliases, like `UK` or the former `ZR`, become the current code (`GB`
+    /// and `CD`). Subtags are already in canonical case, and subtags without a replacement are
+    /// kept as they are. See [`canonicalize`] for tags that can't be parsed into a [`Locale`],
+    /// like `i-klingon`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sys_locale::Locale;
+    ///
+    /// let locale = Locale::parse("iw-il").unwrap();
+    /// assert_eq!(locale.canonical().to_string(), "he-IL");
+    /// ```
+    pub fn canonical(&self) -> Locale {
+        let mut locale = self.clone();
+        if let Some((_, language)) = LANGUAGE_ALIASES.iter().find(|(a, _)| *a == locale.language) {
+            locale.language = String::from(*language);
+        }
+        if let Some(region) = &mut locale.region {
+            if let Some((_, preferred)) = REGION_ALIASES.iter().find(|(a, _)| a == region) {
+                *region = String::from(*preferred);
+            }
+        }
+        locale
+    }
+
+    /// Returns `true` if this locale and `other` are the same after replacing deprecated
+    /// subtags, as done by [`canonical`](Self::canonical).
+    ///
+    /// Plain `==` only ignores case, so `iw-IL` and `he-IL` differ, while this treats them as
+    /// the same locale. This is useful for deduplicating stored preferences that only differ by
+    /// a deprecated spelling. Legacy tags like `zh-CHS` can't be parsed into a [`Locale`], so
+    /// pass them through [`canonicalize`] first to compare them with their modern form.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sys_locale::Locale;
+    ///
+    /// let old = Locale::parse("iw-IL").unwrap();
+    /// let new = Locale::parse("he-IL").unwrap();
+    /// assert_ne!(old, new);
+    /// assert!(old.canonical_eq(&new));
+    /// ```
+    pub fn canonical_eq(&self, other: &Locale) -> bool {
+        self.canonical() == other.canonical()
+    }
+
+    /// Returns this locale with its likely script and region added, such as `zh-Hans-CN` for
+    /// `zh`, following the CLDR "Add Likely Subtags" algorithm.
+    ///
+    /// The locale is [canonicalized](Self::canonical) first. The most specific entry of a
+    /// compact likely subtags table is then used to fill in whichever of the script and the
+    /// region is missing, so `zh-TW` becomes `zh-Hant-TW` while `zh` becomes `zh-Hans-CN`.
+    /// Subtags that are present are never replaced, and variants, extensions, and the private
+    /// use sequence are kept.
+    ///
+    /// The table covers the languages and script or region combinations in common use, from
+    /// CLDR 44. A locale whose language isn't in it, or that is
+    /// [undetermined](Self::is_undetermined), is only canonicalized.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sys_locale::Locale;
+    ///
+    /// let locale = Locale::parse("zh-TW").unwrap();
+    /// assert_eq!(locale.maximize().to_string(), "zh-Hant-TW");
+    /// ```
+    pub fn maximize(&self) -> Locale {
+        let mut locale = self.canonical();
+        if locale.is_undetermined() || (locale.script.is_some() && locale.region.is_some()) {
+            return locale;
+        }
+
+        let language = &locale.language;
+        let mut keys: Vec<String> = Vec::with_capacity(3);
+        if let Some(region) = &locale.region {
+            keys.push(format!("{}-{}", language, region));
+        }
+        if let Some(script) = &locale.script {
+            keys.push(format!("{}-{}", language, script));
+        }
+        keys.push(language.clone());
+
+        let likely = keys
+            .iter()
+            .find_map(|key| LIKELY_SUBTAGS.iter().find(|(k, _, _)| k == key));
+        if let Some((_, script, region)) = likely {
+            locale.script.get_or_insert_with(|| String::from(*script));
+            locale.region.get_or_insert_with(|| String::from(*region));
+        }
+        locale
+    }
+
+    /// Returns this locale with the script and region removed where they're implied by the
+    /// rest, such as `zh` for `zh-Hans-CN`, following the CLDR "Remove Likely Subtags"
+    /// algorithm.
+    ///
+    /// This is the reverse of [`maximize`](Self::maximize): the shortest of the language
+    /// alone, the language and region, or the language and script that maximizes to the same
+    /// locale is returned, so `zh-Hant-TW` becomes `zh-TW`. Variants, extensions, and the
+    /// private use sequence are kept. A locale that can't be maximized is only canonicalized.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sys_locale::Locale;
+    ///
+    /// let locale = Locale::parse("en-Latn-US").unwrap();
+    /// assert_eq!(locale.minimize().to_string(), "en");
+    /// ```
+    pub fn minimize(&self) -> Locale {
+        let max = self.maximize();
+        if max.script.is_none() || max.region.is_none() {
+            return max;
+        }
+
+        let trial = |script: Option<&String>, region: Option<&String>| Locale {
+            language: max.language.clone(),
+            script: script.cloned(),
+            region: region.cloned(),
+            variants: Vec::new(),
+            extensions: Vec::new(),
+            private_use: None,
+        };
+        let trials = [
+            trial(None, None),
+            trial(None, max.region.as_ref()),
+            trial(max.script.as_ref(), None),
+        ];
+        let mut min = trials
+            .iter()
+            .find(|trial| {
+                let maximized = trial.maximize();
+                maximized.script == max.script && maximized.region == max.region
+            })
+            .cloned()
+            .unwrap_or_else(|| trial(max.script.as_ref(), max.region.as_ref()));
+
+        min.variants = max.variants;
+        min.extensions = max.extensions;
+        min.private_use = max.private_use;
+        min
+    }
+
+    /// Converts this locale to a POSIX locale name, such as `sr_RS.UTF-8@latin`, for setting
+    /// `LANG` or an `LC_*` variable.
+    ///
+    /// The result is the language, the region after a `_`, and the `UTF-8` codeset. A script
+    /// or variant with a well-known glibc modifier becomes that modifier, like the `Latn` in
+    /// `sr-Latn-RS` (`@latin`) or the `valencia` in `ca-ES-valencia` (`@valencia`).
+    ///
+    /// The conversion is lossy, since POSIX names can't express most of BCP 47:
+    /// - Scripts and variants without a known modifier are dropped, so `zh-Hant-TW` becomes
+    ///   `zh_TW.UTF-8`. That's usually right, since it's the script implied by the region.
+    /// - Only one modifier is kept, and the script's takes precedence over the variant's.
+    /// - Extensions and the private use sequence are dropped.
+    /// - A tag with only a private use sequence has no language, and produces just the codeset.
+    ///
+    /// The result isn't checked against the locales installed on the system.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sys_locale::Locale;
+    ///
+    /// let locale = Locale::parse("sr-Latn-RS").unwrap();
+    /// assert_eq!(locale.to_posix(), "sr_RS.UTF-8@latin");
+    /// ```
+    pub fn to_posix(&self) -> String {
+        let mut posix = self.language.clone();
+        if let Some(region) = &self.region {
+            posix.push('_');
+            posix.push_str(region);
+        }
+        posix.push_str(".UTF-8");
+
+        let modifier = self
+            .script
+            .as_deref()
+            .and_then(|script| {
+                POSIX_SCRIPT_MODIFIERS
+                    .iter()
+                    .find(|(language, s, _)| *language == self.language && *s == script)
+            })
+            .or_else(|| {
+                POSIX_VARIANT_MODIFIERS
+                    .iter()
+                    .find(|(language, variant, _)| {
+                        *language == self.language && self.variants.iter().any(|v| v == variant)
+                    })
+            });
+        if let Some((_, _, modifier)) = modifier {
+            posix.push('@');
+            posix.push_str(modifier);
+        }
+
+        posix
+    }
+
+    /// Returns this locale with its least significant subtag removed, or [`None`] if only the
+    /// language (or a private use sequence) remains.
+    fn truncate(&self) -> Option<Locale> {
+        let mut locale = self.clone();
+        if locale.language.is_empty() {
+            return None;
+        }
+
+        if locale.private_use.take().is_some()
+            || locale.extensions.pop().is_some()
+            || locale.variants.pop().is_some()
+            || locale.region.take().is_some()
+            || locale.script.take().is_some()
+        {
+            Some(locale)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns `true` if `tag` is a well-formed BCP 47 tag in canonical case.
+///
+/// A canonical tag is one that [`Locale::parse`] would format back unchanged: the script is
+/// titlecase, the region is uppercase, and every other subtag is lowercase. Most platforms
+/// already report tags this way, so checking this first lets callers skip parsing and
+/// reformatting them. The check doesn't allocate.
+///
+/// # Example
+///
+/// ```
+/// use sys_locale::is_canonical;
+///
+/// assert!(is_canonical("zh-Hant-TW"));
+/// assert!(!is_canonical("zh-hant-tw"));
+/// assert!(!is_canonical("en_US"));
+/// ```
+pub fn is_canonical(tag: &str) -> bool {
+    let mut canonical = true;
+    let well_formed = classify(tag, |kind, subtag| {
+        let mut bytes = subtag.bytes();
+        canonical &= match kind {
+            SubtagKind::Script => {
+                bytes.next().map_or(false, |b| !b.is_ascii_lowercase())
+                    && bytes.all(|b| !b.is_ascii_uppercase())
+            }
+            SubtagKind::Region => bytes.all(|b| !b.is_ascii_lowercase()),
+            _ => bytes.all(|b| !b.is_ascii_uppercase()),
+        };
+    });
+    well_formed && canonical
+}
+
+/// Returns `true` if `tag` is a well-formed BCP 47 language tag, in any case.
+///
+/// This is the check [`Locale::parse`] performs, without keeping the subtags around. Like
+/// every function in this module, it doesn't access the system and works on every target.
+///
+/// # Example
+///
+/// ```
+/// use sys_locale::is_valid_bcp47;
+///
+/// assert!(is_valid_bcp47("sr-latn-RS"));
+/// assert!(!is_valid_bcp47("en_US"));
+/// assert!(!is_valid_bcp47("en-US-"));
+/// ```
+pub fn is_valid_bcp47(tag: &str) -> bool {
+    classify(tag, |_, _| {})
+}
+
+/// Returns the canonical form of `tag`, or [`None`] if it isn't well-formed and has no known
+/// replacement.
+///
+/// This is the single entry point for normalizing a tag:
+/// - Whole tags that can't be parsed into subtags, like the grandfathered `i-klingon` or the
+///   legacy Windows names `zh-CHS` and `zh-CHT`, are replaced by their preferred value.
+/// - Deprecated language subtags and region aliases are replaced, see [`Locale::canonical`].
+/// - The language, variants, and extensions are lowercased, the script is titlecased, and the
+///   region is uppercased.
+///
+/// [`Resolver::canonicalize`](crate::Resolver::canonicalize) applies this to the locales
+/// reported by the system. Tags that are canonical already, as most platforms report them, are
+/// recognized with [`is_canonical`] and copied without being parsed.
+///
+/// # Example
+///
+/// ```
+/// use sys_locale::canonicalize;
+///
+/// assert_eq!(canonicalize("ZH-hant-tw").as_deref(), Some("zh-Hant-TW"));
+/// assert_eq!(canonicalize("iw-IL").as_deref(), Some("he-IL"));
+/// assert_eq!(canonicalize("en-uk").as_deref(), Some("en-GB"));
+/// assert_eq!(canonicalize("i-klingon").as_deref(), Some("tlh"));
+/// assert_eq!(canonicalize("en_US"), None);
+/// ```
+pub fn canonicalize(tag: &str) -> Option<String> {
+    if let Some((_, preferred)) = TAG_ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(tag))
+    {
+        return Some(String::from(*preferred));
+    }
+    if is_canonical_unaliased(tag) {
+        return Some(String::from(tag));
+    }
+    Locale::parse(tag).map(|locale| locale.canonical().to_string())
+}
+
+/// Like [`canonicalize`], but takes ownership of `tag` and returns it as it is when it's
+/// canonical already, which is what the Windows and Unix providers almost always report.
+///
+/// Returns `tag` back as the error if it has no canonical form.
+pub(crate) fn canonicalize_owned(tag: String) -> Result<String, String> {
+    if is_canonical_unaliased(&tag) {
+        return Ok(tag);
+    }
+    canonicalize(&tag).ok_or(tag)
+}
+
+/// Returns `true` if `tag` is [canonical](is_canonical) and none of its subtags or the tag as a
+/// whole has a replacement, so [`canonicalize`] would return it unchanged. The check doesn't
+/// allocate.
+fn is_canonical_unaliased(tag: &str) -> bool {
+    if !is_canonical(tag)
+        || TAG_ALIASES
+            .iter()
+            .any(|(alias, _)| alias.eq_ignore_ascii_case(tag))
+    {
+        return false;
+    }
+    // The subtags are in canonical case, so they can be compared with the tables directly.
+    let mut aliased = false;
+    classify(tag, |kind, subtag| {
+        aliased |= match kind {
+            SubtagKind::Language => LANGUAGE_ALIASES.iter().any(|(alias, _)| *alias == subtag),
+            SubtagKind::Region => REGION_ALIASES.iter().any(|(alias, _)| *alias == subtag),
+            _ => false,
+        };
+    });
+    !aliased
+}
+
+/// Normalizes a tag reported by a platform that doesn't guarantee its format, like a browser's
+/// `navigator.languages`, to the form the native providers return.
+///
+/// POSIX names like `en_US` are converted like on Unix, and the case of every subtag is fixed,
+/// so that `en-us` and `en-US` are recognized as the same locale when repeats are skipped.
+/// Deprecated subtags are kept, like everywhere else outside of [`canonicalize`]. Tags that
+/// aren't well-formed afterwards are dropped, like on Windows.
+///
+/// [Undetermined](Locale::is_undetermined) tags like `und`, which some browsers report in
+/// privacy modes instead of the user's language, are dropped too, so that the provider falls
+/// through to its next source instead of returning a locale that names no language.
+#[cfg(any(
+    all(
+        target_family = "wasm",
+        any(feature = "js", feature = "js-minimal", feature = "deno"),
+        not(unix)
+    ),
+    test
+))]
+pub(crate) fn normalize_reported(tag: &str) -> Option<String> {
+    let tag = crate::posix::posix_to_bcp47(tag)?;
+    Locale::parse(&tag)
+        .filter(|locale| !locale.is_undetermined())
+        .map(|locale| locale.to_string())
+}
+
+/// Returns `tag` with its likely script and region added, such as `en-Latn-US` for `en`.
+///
+/// Returns [`None`] if `tag` isn't well-formed. See [`Locale::maximize`].
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(sys_locale::maximize("zh").as_deref(), Some("zh-Hans-CN"));
+/// assert_eq!(sys_locale::maximize("sr-ME").as_deref(), Some("sr-Latn-ME"));
+/// ```
+pub fn maximize(tag: &str) -> Option<String> {
+    Locale::parse(tag).map(|locale| locale.maximize().to_string())
+}
+
+/// Returns `tag` without the script and region implied by the rest, such as `en` for
+/// `en-Latn-US`.
+///
+/// Returns [`None`] if `tag` isn't well-formed. See [`Locale::minimize`].
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(sys_locale::minimize("zh-Hant-TW").as_deref(), Some("zh-TW"));
+/// assert_eq!(sys_locale::minimize("pt-Latn-BR").as_deref(), Some("pt"));
+/// ```
+pub fn minimize(tag: &str) -> Option<String> {
+    Locale::parse(tag).map(|locale| locale.minimize().to_string())
+}
+
+/// Returns `tag` with its script subtag added or replaced by `script`, in canonical form.
+///
+/// Returns [`None`] if `tag` isn't well-formed or `script` isn't a valid script subtag. See
+/// [`Locale::with_script`].
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(sys_locale::set_script("zh-TW", "hant").as_deref(), Some("zh-Hant-TW"));
+/// assert_eq!(sys_locale::set_script("sr-Cyrl", "Latn").as_deref(), Some("sr-Latn"));
+/// ```
+pub fn set_script(tag: &str, script: &str) -> Option<String> {
+    Some(Locale::parse(tag)?.with_script(script)?.to_string())
+}
+
+/// Returns `tag` with its region subtag added or replaced by `region`, in canonical form.
+///
+/// Returns [`None`] if `tag` isn't well-formed or `region` isn't a valid region subtag. See
+/// [`Locale::with_region`].
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(sys_locale::set_region("en", "GB").as_deref(), Some("en-GB"));
+/// assert_eq!(sys_locale::set_region("es-ES", "419").as_deref(), Some("es-419"));
+/// assert_eq!(sys_locale::set_region("en_US", "GB"), None);
+/// ```
+pub fn set_region(tag: &str, region: &str) -> Option<String> {
+    Some(Locale::parse(tag)?.with_region(region)?.to_string())
+}
+
+/// Converts a BCP 47 tag to a POSIX locale name, such as `en_US.UTF-8`.
+///
+/// This is the reverse of how the Unix environment variables are read, for passing a locale on
+/// to other programs. See [`Locale::to_posix`] for how each subtag is converted and what is
+/// lost along the way. If `tag` isn't well-formed, it's converted by replacing every `-` with
+/// `_` and appending the codeset, which is what POSIX expects for `en-US` anyway.
+///
+/// # Example
+///
+/// ```
+/// use sys_locale::to_posix;
+///
+/// assert_eq!(to_posix("de-DE"), "de_DE.UTF-8");
+/// assert_eq!(to_posix("ca-ES-valencia"), "ca_ES.UTF-8@valencia");
+/// ```
+pub fn to_posix(tag: &str) -> String {
+    match Locale::parse(tag) {
+        Some(locale) => locale.to_posix(),
+        None => {
+            let mut posix = tag.replace('-', "_");
+            posix.push_str(".UTF-8");
+            posix
+        }
+    }
+}
+
+/// Returns `true` if `tag` has the same primary language as `language`, ignoring every other
+/// subtag and case.
+///
+/// `language` may be a full tag itself, in which case only its language is compared, so
+/// `de-AT` matches both `de` and `de-CH`. Tags that aren't well-formed, have no language, or are
+/// [undetermined](Locale::is_undetermined) match nothing.
+///
+/// # Example
+///
+/// ```
+/// use sys_locale::matches_language;
+///
+/// assert!(matches_language("de-CH", "de"));
+/// assert!(matches_language("zh-Hant-TW", "ZH"));
+/// assert!(!matches_language("en-US", "de"));
+/// ```
+pub fn matches_language(tag: &str, language: &str) -> bool {
+    let primary = |tag: &str| {
+        Locale::parse(tag)
+            .filter(|locale| !locale.is_undetermined())
+            .and_then(|locale| locale.language_typed())
+    };
+    match (primary(tag), primary(language)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Returns `tag` followed by its progressively less specific fallbacks.
+///
+/// For example, `zh-Hant-TW` yields `["zh-Hant-TW", "zh-Hant", "zh"]`. The first entry is
+/// always `tag` as given, while the fallbacks use canonical casing. If `tag` isn't a
+/// well-formed BCP 47 tag, it's returned on its own.
+///
+/// [Undetermined](Locale::is_undetermined) tags like `und` match no language, so the chain is
+/// empty for them.
+///
+/// See [`Locale::fallbacks`] for the truncation rules.
+pub fn fallback_chain(tag: &str) -> Vec<String> {
+    match Locale::parse(tag) {
+        Some(locale) if locale.is_undetermined() => Vec::new(),
+        Some(locale) => {
+            let mut chain = vec![String::from(tag)];
+            chain.extend(locale.fallbacks().skip(1).map(|l| l.to_string()));
+            chain
+        }
+        None => vec![String::from(tag)],
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (_, subtag) in self.subtags() {
+            if !first {
+                f.write_str("-")?;
+            }
+            f.write_str(subtag)?;
+            first = false;
+        }
+        Ok(())
+    }
+}
+
+/// Iterates over the locale's [fallbacks](Locale::fallbacks), most specific first.
+///
+/// The first item is the locale itself, and the last is its bare language subtag, so loading
+/// resources reads as a plain loop:
+///
+/// ```
+/// use sys_locale::Locale;
+///
+/// let locale = Locale::parse("zh-Hant-TW").unwrap();
+/// let mut tried = Vec::new();
+/// for candidate in &locale {
+///     tried.push(candidate.to_string());
+/// }
+/// assert_eq!(tried, ["zh-Hant-TW", "zh-Hant", "zh"]);
+/// ```
+impl IntoIterator for &Locale {
+    type Item = Locale;
+    type IntoIter = Fallbacks;
+
+    fn into_iter(self) -> Fallbacks {
+        self.fallbacks()
+    }
+}
+
+/// Creates a [`Locale`] from individual subtags, created by [`Locale::builder`].
+///
+/// Every subtag is validated by [`build`](Self::build), and the resulting locale uses
+/// canonical case like a parsed one, so its string form is always [canonical](is_canonical).
+///
+/// # Example
+///
+/// ```
+/// use sys_locale::Locale;
+///
+/// let locale = Locale::builder()
+///     .language("CA")
+///     .region("es")
+///     .variant("valencia")
+///     .build()
+///     .unwrap();
+/// assert_eq!(locale.to_string(), "ca-ES-valencia");
+///
+/// assert_eq!(Locale::builder().language("en").region("USA").build(), None);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LocaleBuilder {
+    language: Option<String>,
+    script: Option<String>,
+    region: Option<String>,
+    variants: Vec<String>,
+}
+
+impl LocaleBuilder {
+    /// Sets the primary language subtag, such as `en`. This is required.
+    pub fn language(mut self, language: &str) -> Self {
+        self.language = Some(String::from(language));
+        self
+    }
+
+    /// Sets the script subtag, such as `Latn`.
+    pub fn script(mut self, script: &str) -> Self {
+        self.script = Some(String::from(script));
+        self
+    }
+
+    /// Sets the region subtag, such as `US` or `419`.
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = Some(String::from(region));
+        self
+    }
+
+    /// Adds a variant subtag, such as `valencia`. Variants are kept in the order they're added.
+    pub fn variant(mut self, variant: &str) -> Self {
+        self.variants.push(String::from(variant));
+        self
+    }
+
+    /// Validates the subtags and creates the locale.
+    ///
+    /// Returns [`None`] if no language was set, any subtag isn't valid for its position, or the
+    /// same variant was added twice.
+    pub fn build(&self) -> Option<Locale> {
+        let language = self.language.as_deref().filter(|l| is_language(l))?;
+        if !self.script.as_deref().map_or(true, is_script)
+            || !self.region.as_deref().map_or(true, is_region)
+        {
+            return None;
+        }
+
+        let mut variants: Vec<String> = Vec::with_capacity(self.variants.len());
+        for variant in &self.variants {
+            let variant = variant.to_ascii_lowercase();
+            if !is_variant(&variant) || variants.contains(&variant) {
+                return None;
+            }
+            variants.push(variant);
+        }
+
+        Some(Locale {
+            language: language.to_ascii_lowercase(),
+            script: self.script.as_deref().map(titlecase),
+            region: self.region.as_deref().map(str::to_ascii_uppercase),
+            variants,
+            extensions: Vec::new(),
+            private_use: None,
+        })
+    }
+}
+
+/// An iterator over a [`Locale`] and its fallbacks, created by [`Locale::fallbacks`].
+#[derive(Clone, Debug)]
+pub struct Fallbacks {
+    next: Option<Locale>,
+}
+
+impl Iterator for Fallbacks {
+    type Item = Locale;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        self.next = current.truncate();
+        Some(current)
+    }
+}
+
+/// An iterator over the subtags of a [`Locale`], created by [`Locale::subtags`].
+#[derive(Clone, Debug)]
+pub struct Subtags<'a> {
+    inner: vec::IntoIter<(SubtagKind, &'a str)>,
+}
+
+impl<'a> Iterator for Subtags<'a> {
+    type Item = (SubtagKind, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Subtags<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl ExactSizeIterator for Subtags<'_> {}
+
+/// Passes every subtag of `tag` to `visit` along with its role, returning `false` if the tag
+/// isn't well-formed.
+///
+/// Singletons are passed as the first subtag of their sequence. Subtags are passed in their
+/// original case, and `visit` may have been called for a prefix of a malformed tag. Duplicate
+/// variants and singletons are found by looking back through `tag`, so that nothing needs to be
+/// allocated.
+fn classify<'a>(tag: &'a str, mut visit: impl FnMut(SubtagKind, &'a str)) -> bool {
+    let mut subtags = tag.split('-').peekable();
+    // The byte offset of the subtag at the front of `subtags`.
+    let mut offset = 0;
+    let seen = |start: usize, end: usize, subtag: &str| {
+        tag.get(start..end.saturating_sub(1))
+            .map_or(false, |before| {
+                before.split('-').any(|s| s.eq_ignore_ascii_case(subtag))
+            })
+    };
+
+    let first = match subtags.peek() {
+        Some(first) => *first,
+        None => return false,
+    };
+    if is_language(first) {
+        visit(SubtagKind::Language, first);
+        offset += first.len() + 1;
+        subtags.next();
+    } else if !first.eq_ignore_ascii_case("x") {
+        return false;
+    }
+
+    if let Some(script) = subtags.next_if(|s| is_script(s)) {
+        visit(SubtagKind::Script, script);
+        offset += script.len() + 1;
+    }
+    if let Some(region) = subtags.next_if(|s| is_region(s)) {
+        visit(SubtagKind::Region, region);
+        offset += region.len() + 1;
+    }
+
+    let variants = offset;
+    while let Some(variant) = subtags.next_if(|s| is_variant(s)) {
+        if seen(variants, offset, variant) {
+            return false;
+        }
+        visit(SubtagKind::Variant, variant);
+        offset += variant.len() + 1;
+    }
+
+    let extensions = offset;
+    while let Some(singleton) = subtags.next() {
+        if singleton.len() != 1 || !singleton.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return false;
+        }
+
+        let is_private = singleton.eq_ignore_ascii_case("x");
+        // Extension subtags are at least two characters long, so the only single character
+        // subtags before this one are earlier singletons.
+        if !is_private && seen(extensions, offset, singleton) {
+            return false;
+        }
+        let kind = if is_private {
+            SubtagKind::PrivateUse
+        } else {
+            SubtagKind::Extension
+        };
+        visit(kind, singleton);
+        offset += singleton.len() + 1;
+
+        let mut count = 0;
+        while let Some(subtag) = subtags.next_if(|s| {
+            if is_private {
+                is_alphanumeric(s, 1, 8)
+            } else {
+                is_alphanumeric(s, 2, 8)
+            }
+        }) {
+            visit(kind, subtag);
+            offset += subtag.len() + 1;
+            count += 1;
+        }
+        if count == 0 {
+            return false;
+        }
+
+        if is_private {
+            // The private use sequence swallows everything after it.
+            break;
+        }
+    }
+
+    subtags.next().is_none()
+}
+
+/// Appends `subtag` to a sequence of subtags, separated by `-`.
+fn append(sequence: Option<&mut String>, subtag: &str) {
+    if let Some(sequence) = sequence {
+        sequence.push('-');
+        sequence.push_str(subtag);
+    }
+}
+
+/// Whole tags with a preferred value, for those that aren't made of regular subtags:
+/// `(tag, preferred)`.
+///
+/// These are the grandfathered and redundant tags of the IANA registry that have a
+/// replacement, along with the legacy names Windows used for Chinese scripts.
+const TAG_ALIASES: &[(&str, &str)] = &[
+    ("art-lojban", "jbo"),
+    ("en-GB-oed", "en-GB-oxendict"),
+    ("i-ami", "ami"),
+    ("i-bnn", "bnn"),
+    ("i-hak", "hak"),
+    ("i-klingon", "tlh"),
+    ("i-lux", "lb"),
+    ("i-navajo", "nv"),
+    ("i-pwn", "pwn"),
+    ("i-tao", "tao"),
+    ("i-tay", "tay"),
+    ("i-tsu", "tsu"),
+    ("no-bok", "nb"),
+    ("no-nyn", "nn"),
+    ("sgn-BE-FR", "sfb"),
+    ("sgn-BE-NL", "vgt"),
+    ("sgn-CH-DE", "sgg"),
+    ("zh-CHS", "zh-Hans"),
+    ("zh-CHT", "zh-Hant"),
+    ("zh-guoyu", "cmn"),
+    ("zh-hakka", "hak"),
+    ("zh-min-nan", "nan"),
+    ("zh-xiang", "hsn"),
+];
+
+/// Deprecated language subtags and their preferred values: `(deprecated, preferred)`.
+pub(crate) const LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("in", "id"),
+    ("iw", "he"),
+    ("ji", "yi"),
+    ("jw", "jv"),
+    ("mo", "ro"),
+];
+
+/// Deprecated or informal region subtags and their preferred values: `(alias, preferred)`.
+///
+/// `UK` was never a valid region subtag, but it's a common mistake for `GB`.
+const REGION_ALIASES: &[(&str, &str)] = &[
+    ("BU", "MM"),
+    ("DD", "DE"),
+    ("FX", "FR"),
+    ("TP", "TL"),
+    ("UK", "GB"),
+    ("YD", "YE"),
+    ("ZR", "CD"),
+];
+
+/// The likely script and region of a language, alone or with a script or region:
+/// `(key, script, region)`.
+///
+/// This is a subset of the likely subtags of CLDR 44, covering the languages in common use and
+/// the combinations where the script or region changes the other one, like `zh-TW`. Keys are
+/// in canonical case.
+const LIKELY_SUBTAGS: &[(&str, &str, &str)] = &[
+    ("af", "Latn", "ZA"),
+    ("am", "Ethi", "ET"),
+    ("ar", "Arab", "EG"),
+    ("az", "Latn", "AZ"),
+    ("az-Arab", "Arab", "IR"),
+    ("az-IR", "Arab", "IR"),
+    ("be", "Cyrl", "BY"),
+    ("bg", "Cyrl", "BG"),
+    ("bn", "Beng", "BD"),
+    ("bs", "Latn", "BA"),
+    ("ca", "Latn", "ES"),
+    ("cs", "Latn", "CZ"),
+    ("cy", "Latn", "GB"),
+    ("da", "Latn", "DK"),
+    ("de", "Latn", "DE"),
+    ("el", "Grek", "GR"),
+    ("en", "Latn", "US"),
+    ("eo", "Latn", "001"),
+    ("es", "Latn", "ES"),
+    ("et", "Latn", "EE"),
+    ("eu", "Latn", "ES"),
+    ("fa", "Arab", "IR"),
+    ("fi", "Latn", "FI"),
+    ("fil", "Latn", "PH"),
+    ("fr", "Latn", "FR"),
+    ("ga", "Latn", "IE"),
+    ("gl", "Latn", "ES"),
+    ("gu", "Gujr", "IN"),
+    ("ha", "Latn", "NG"),
+    ("he", "Hebr", "IL"),
+    ("hi", "Deva", "IN"),
+    ("hr", "Latn", "HR"),
+    ("hu", "Latn", "HU"),
+    ("hy", "Armn", "AM"),
+    ("id", "Latn", "ID"),
+    ("ig", "Latn", "NG"),
+    ("is", "Latn", "IS"),
+    ("it", "Latn", "IT"),
+    ("ja", "Jpan", "JP"),
+    ("ka", "Geor", "GE"),
+    ("kk", "Cyrl", "KZ"),
+    ("km", "Khmr", "KH"),
+    ("kn", "Knda", "IN"),
+    ("ko", "Kore", "KR"),
+    ("ku", "Latn", "TR"),
+    ("ky", "Cyrl", "KG"),
+    ("la", "Latn", "VA"),
+    ("lb", "Latn", "LU"),
+    ("lo", "Laoo", "LA"),
+    ("lt", "Latn", "LT"),
+    ("lv", "Latn", "LV"),
+    ("mk", "Cyrl", "MK"),
+    ("ml", "Mlym", "IN"),
+    ("mn", "Cyrl", "MN"),
+    ("mn-CN", "Mong", "CN"),
+    ("mn-Mong", "Mong", "CN"),
+    ("mr", "Deva", "IN"),
+    ("ms", "Latn", "MY"),
+    ("mt", "Latn", "MT"),
+    ("my", "Mymr", "MM"),
+    ("nb", "Latn", "NO"),
+    ("ne", "Deva", "NP"),
+    ("nl", "Latn", "NL"),
+    ("nn", "Latn", "NO"),
+    ("no", "Latn", "NO"),
+    ("pa", "Guru", "IN"),
+    ("pa-Arab", "Arab", "PK"),
+    ("pa-PK", "Arab", "PK"),
+    ("pl", "Latn", "PL"),
+    ("ps", "Arab", "AF"),
+    ("pt", "Latn", "BR"),
+    ("ro", "Latn", "RO"),
+    ("ru", "Cyrl", "RU"),
+    ("si", "Sinh", "LK"),
+    ("sk", "Latn", "SK"),
+    ("sl", "Latn", "SI"),
+    ("so", "Latn", "SO"),
+    ("sq", "Latn", "AL"),
+    ("sr", "Cyrl", "RS"),
+    ("sr-ME", "Latn", "ME"),
+    ("sv", "Latn", "SE"),
+    ("sw", "Latn", "TZ"),
+    ("ta", "Taml", "IN"),
+    ("te", "Telu", "IN"),
+    ("tg", "Cyrl", "TJ"),
+    ("th", "Thai", "TH"),
+    ("tk", "Latn", "TM"),
+    ("tr", "Latn", "TR"),
+    ("uk", "Cyrl", "UA"),
+    ("ur", "Arab", "PK"),
+    ("uz", "Latn", "UZ"),
+    ("uz-AF", "Arab", "AF"),
+    ("uz-Arab", "Arab", "AF"),
+    ("vi", "Latn", "VN"),
+    ("yo", "Latn", "NG"),
+    ("zh", "Hans", "CN"),
+    ("zh-HK", "Hant", "HK"),
+    ("zh-Hant", "Hant", "TW"),
+    ("zh-MO", "Hant", "MO"),
+    ("zh-TW", "Hant", "TW"),
+    ("zu", "Latn", "ZA"),
+];
+
+/// Scripts with a glibc modifier, by language: `(language, script, modifier)`.
+///
+/// Only scripts that aren't the default for the language in glibc are listed, since the
+/// default one is never spelled out as a modifier.
+pub(crate) const POSIX_SCRIPT_MODIFIERS: &[(&str, &str, &str)] = &[
+    ("be", "Latn", "latin"),
+    ("ks", "Deva", "devanagari"),
+    ("sd", "Deva", "devanagari"),
+    ("sr", "Latn", "latin"),
+    ("uz", "Cyrl", "cyrillic"),
+];
+
+/// Variants with a glibc modifier, by language: `(language, variant, modifier)`.
+pub(crate) const POSIX_VARIANT_MODIFIERS: &[(&str, &str, &str)] = &[("ca", "valencia", "valencia")];
+
+fn is_alphanumeric(subtag: &str, min: usize, max: usize) -> bool {
+    (min..=max).contains(&subtag.len()) && subtag.bytes().all(|b| b.is_ascii_alphanumeric())
+}
+
+fn is_alphabetic(subtag: &str) -> bool {
+    subtag.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+/// `2*3ALPHA / 5*8ALPHA`. Four letter language subtags are reserved for future use.
+pub(crate) fn is_language(subtag: &str) -> bool {
+    matches!(subtag.len(), 2 | 3 | 5..=8) && is_alphabetic(subtag)
+}
+
+/// `4ALPHA`
+fn is_script(subtag: &str) -> bool {
+    subtag.len() == 4 && is_alphabetic(subtag)
+}
+
+/// `2ALPHA / 3DIGIT`
+pub(crate) fn is_region(subtag: &str) -> bool {
+    match subtag.len() {
+        2 => is_alphabetic(subtag),
+        3 => subtag.bytes().all(|b| b.is_ascii_digit()),
+        _ => false,
+    }
+}
+
+/// `5*8alphanum / (DIGIT 3alphanum)`
+fn is_variant(subtag: &str) -> bool {
+    match subtag.len() {
+        4 => subtag.as_bytes()[0].is_ascii_digit() && is_alphanumeric(subtag, 4, 4),
+        _ => is_alphanumeric(subtag, 5, 8),
+    }
+}
+
+fn titlecase(subtag: &str) -> String {
+    let mut out = subtag.to_ascii_lowercase();
+    out[..1].make_ascii_uppercase();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        canonicalize, canonicalize_owned, fallback_chain, is_canonical, is_valid_bcp47,
+        matches_language, maximize, minimize, normalize_reported, set_region, set_script, to_posix,
+        Locale, SubtagKind, LIKELY_SUBTAGS,
+    };
+    use alloc::{
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    #[test]
+    fn parse_well_formed() {
+        for (tag, expected) in [
+            ("en", "en"),
+            ("EN-us", "en-US"),
+            ("zh-hans-cn", "zh-Hans-CN"),
+            ("es-419", "es-419"),
+            ("ca-ES-valencia", "ca-ES-valencia"),
+            ("de-CH-1996", "de-CH-1996"),
+            ("ja-JP-u-ca-japanese", "ja-JP-u-ca-japanese"),
+            ("en-US-u-ca-gregory-t-ja", "en-US-u-ca-gregory-t-ja"),
+            ("en-US-x-Custom", "en-US-x-custom"),
+            ("x-klingon", "x-klingon"),
+            // Private use sequences don't follow the shape of the other subtags
+            ("x-foo", "x-foo"),
+            ("X-FOO-1", "x-foo-1"),
+            ("en-US-x-private", "en-US-x-private"),
+            ("en-x-a-b-12345678", "en-x-a-b-12345678"),
+            ("de-u-co-phonebk-X-US", "de-u-co-phonebk-x-us"),
+        ] {
+            let locale = Locale::parse(tag).unwrap_or_else(|| panic!("{} was rejected", tag));
+            assert_eq!(locale.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn parse_malformed() {
+        for tag in [
+            "",
+            "e",
+            "languages-US",
+            "en_US",
+            "en-",
+            "en--US",
+            "-US",
+            "en-USA",
+            "en-US-u",
+            "en-US-u-x-foo",
+            "en-x",
+            "en-u-ca-u-nu-latn",
+            "de-1996-1996",
+            "en-US-a-b",
+            "x",
+            "x-",
+            "X-",
+            "x--foo",
+            "x-123456789",
+            "en-US-x-",
+        ] {
+            assert_eq!(Locale::parse(tag), None, "{} was accepted", tag);
+        }
+    }
+
+    #[test]
+    fn normalizes_reported() {
+        for (tag, expected) in [
+            ("en-US", Some("en-US")),
+            ("en-us", Some("en-US")),
+            ("EN_us", Some("en-US")),
+            (" zh-hant-tw ", Some("zh-Hant-TW")),
+            ("iw-IL", Some("iw-IL")),
+            ("C", None),
+            ("", None),
+            ("en--US", None),
+            ("und", None),
+            ("UND-us", None),
+        ] {
+            assert_eq!(normalize_reported(tag).as_deref(), expected, "{}", tag);
+        }
+    }
+
+    #[test]
+    fn parse_lossy() {
+        for (tag, expected) in [
+            ("en-US-", "en-US"),
+            ("en--US", "en-US"),
+            ("-en-us", "en-US"),
+            ("EN_us", "en-US"),
+            ("  zh_hant_tw \n", "zh-Hant-TW"),
+            ("sr--Latn--RS--x--custom-", "sr-Latn-RS-x-custom"),
+            ("i-klingon-", "tlh"),
+            ("en-uk", "en-GB"),
+        ] {
+            assert_eq!(
+                Locale::parse_lossy(tag).map(|l| l.to_string()).as_deref(),
+                Some(expected),
+                "{}",
+                tag
+            );
+        }
+
+        for tag in ["", "-", "--", "en-USA", "en_US.UTF-8", "x-"] {
+            assert_eq!(Locale::parse_lossy(tag), None, "{} was accepted", tag);
+        }
+    }
+
+    #[test]
+    fn orders_by_subtags() {
+        let parse = |tag| Locale::parse(tag).unwrap();
+        assert!(parse("en") < parse("en-US"));
+        assert!(parse("en-US") < parse("en-US-x-foo"));
+        assert!(parse("en-us") == parse("EN-US"));
+
+        let mut locales: Vec<Locale> = ["fr", "en-US-x-foo", "en-Latn", "en-US", "de-CH", "en"]
+            .iter()
+            .map(|tag| parse(tag))
+            .collect();
+        locales.sort();
+        let sorted: Vec<_> = locales.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            sorted,
+            ["de-CH", "en", "en-US", "en-US-x-foo", "en-Latn", "fr"]
+        );
+    }
+
+    #[test]
+    fn subtags() {
+        let locale = Locale::parse("sr-latn-rs-ekavsk-u-nu-latn-x-a-bc").unwrap();
+        let subtags: Vec<_> = locale.subtags().collect();
+        assert_eq!(
+            subtags,
+            [
+                (SubtagKind::Language, "sr"),
+                (SubtagKind::Script, "Latn"),
+                (SubtagKind::Region, "RS"),
+                (SubtagKind::Variant, "ekavsk"),
+                (SubtagKind::Extension, "u"),
+                (SubtagKind::Extension, "nu"),
+                (SubtagKind::Extension, "latn"),
+                (SubtagKind::PrivateUse, "x"),
+                (SubtagKind::PrivateUse, "a"),
+                (SubtagKind::PrivateUse, "bc"),
+            ]
+        );
+        assert_eq!(locale.subtags().len(), subtags.len());
+        assert_eq!(locale.subtag(3), Some((SubtagKind::Variant, "ekavsk")));
+        assert_eq!(locale.subtag(10), None);
+
+        let private = Locale::parse("x-klingon").unwrap();
+        assert_eq!(private.language(), "");
+        assert_eq!(private.subtag(0), Some((SubtagKind::PrivateUse, "x")));
+    }
+
+    #[test]
+    fn fallbacks() {
+        for (tag, expected) in [
+            ("en", &["en"] as &[&str]),
+            ("fr-CA", &["fr-CA", "fr"]),
+            ("zh-Hant-TW", &["zh-Hant-TW", "zh-Hant", "zh"]),
+            (
+                "ca-ES-valencia-u-nu-latn-x-priv",
+                &[
+                    "ca-ES-valencia-u-nu-latn-x-priv",
+                    "ca-ES-valencia-u-nu-latn",
+                    "ca-ES-valencia",
+                    "ca-ES",
+                    "ca",
+                ],
+            ),
+            ("x-klingon", &["x-klingon"]),
+            ("fr-ca", &["fr-ca", "fr"]),
+            ("not a tag", &["not a tag"]),
+            ("und", &[]),
+            ("UND-US", &[]),
+        ] {
+            assert_eq!(fallback_chain(tag), expected);
+        }
+
+        let locale = Locale::parse("sr-Latn-RS").unwrap();
+        let mut candidates = Vec::new();
+        for candidate in &locale {
+            candidates.push(candidate.to_string());
+        }
+        assert_eq!(candidates, ["sr-Latn-RS", "sr-Latn", "sr"]);
+        assert_eq!((&Locale::parse("und").unwrap()).into_iter().count(), 0);
+    }
+
+    #[test]
+    fn extensions() {
+        let locale = Locale::parse("de-DE-u-attr-co-phonebk-kn-ca-islamic-civil-T-en").unwrap();
+        assert_eq!(
+            locale.extension('u'),
+            Some("attr-co-phonebk-kn-ca-islamic-civil")
+        );
+        assert_eq!(locale.extension('T'), Some("en"));
+        assert_eq!(locale.extension('x'), None);
+
+        assert_eq!(locale.unicode_extension("co"), Some("phonebk"));
+        assert_eq!(locale.unicode_extension("KN"), Some(""));
+        assert_eq!(locale.unicode_extension("ca"), Some("islamic-civil"));
+        assert_eq!(locale.unicode_extension("attr"), None);
+        assert_eq!(locale.unicode_extension("nu"), None);
+
+        let locale = Locale::parse("en-u-hc-h12-x-nu-arab").unwrap();
+        assert_eq!(locale.unicode_extension("hc"), Some("h12"));
+        assert_eq!(locale.unicode_extension("nu"), None);
+
+        let locale = Locale::parse("en-US-u-kn").unwrap();
+        assert_eq!(locale.unicode_extension("kn"), Some(""));
+        assert_eq!(
+            Locale::parse("en-US").unwrap().unicode_extension("kn"),
+            None
+        );
+    }
+
+    #[test]
+    fn language_region() {
+        for (tag, key) in [
+            ("en-US", "en-US"),
+            ("en", "en"),
+            ("zh-Hans-CN-u-nu-latn", "zh-CN"),
+            ("sr-Latn", "sr"),
+            ("ca-es-valencia", "ca-ES"),
+            ("es-419-x-custom", "es-419"),
+            ("x-klingon", ""),
+        ] {
+            assert_eq!(
+                Locale::parse(tag).unwrap().language_region(),
+                key,
+                "{}",
+                tag
+            );
+        }
+    }
+
+    #[test]
+    fn builds() {
+        let build = |language: &str, script: &str, region: &str, variants: &[&str]| {
+            let mut builder = Locale::builder().language(language);
+            if !script.is_empty() {
+                builder = builder.script(script);
+            }
+            if !region.is_empty() {
+                builder = builder.region(region);
+            }
+            for variant in variants {
+                builder = builder.variant(variant);
+            }
+            builder.build().map(|locale| locale.to_string())
+        };
+
+        assert_eq!(build("en", "", "US", &[]).as_deref(), Some("en-US"));
+        assert_eq!(
+            build("SR", "latn", "rs", &[]).as_deref(),
+            Some("sr-Latn-RS")
+        );
+        assert_eq!(
+            build("sl", "", "", &["ROZAJ", "biske"]).as_deref(),
+            Some("sl-rozaj-biske")
+        );
+        assert_eq!(build("es", "", "419", &[]).as_deref(), Some("es-419"));
+
+        // Invalid subtags
+        assert_eq!(build("en", "", "USA", &[]), None);
+        assert_eq!(build("e", "", "US", &[]), None);
+        assert_eq!(build("en", "Lat", "", &[]), None);
+        assert_eq!(build("en", "", "", &["val"]), None);
+        assert_eq!(build("en", "", "", &["1996", "1996"]), None);
+        assert_eq!(build("en-US", "", "", &[]), None);
+        assert_eq!(Locale::builder().region("US").build(), None);
+
+        // Built locales are canonical and equal to parsed ones
+        let locale = Locale::new("DE", "at").unwrap();
+        assert!(is_canonical(&locale.to_string()));
+        assert_eq!(Some(locale), Locale::parse("de-AT"));
+    }
+
+    #[test]
+    fn language_matches() {
+        assert!(matches_language("de", "de"));
+        assert!(matches_language("de-AT", "de"));
+        assert!(matches_language("DE-at", "de-CH"));
+        assert!(matches_language("zh-Hant-TW", "zh-Hans"));
+        assert!(!matches_language("en-US", "de"));
+        assert!(!matches_language("und-DE", "und"));
+        assert!(!matches_language("x-klingon", "x-klingon"));
+        assert!(!matches_language("de_DE", "de"));
+        assert!(!matches_language("de", ""));
+    }
+
+    #[test]
+    fn maximizes() {
+        for (tag, max) in [
+            ("en", "en-Latn-US"),
+            ("en-GB", "en-Latn-GB"),
+            ("de", "de-Latn-DE"),
+            ("fr-CA", "fr-Latn-CA"),
+            ("ja", "ja-Jpan-JP"),
+            ("zh", "zh-Hans-CN"),
+            ("zh-TW", "zh-Hant-TW"),
+            ("zh-Hant", "zh-Hant-TW"),
+            ("zh-Hant-HK", "zh-Hant-HK"),
+            ("zh-SG", "zh-Hans-SG"),
+            ("sr", "sr-Cyrl-RS"),
+            ("sr-Latn", "sr-Latn-RS"),
+            ("sr-ME", "sr-Latn-ME"),
+            ("pa-PK", "pa-Arab-PK"),
+            ("iw", "he-Hebr-IL"),
+            ("pt-u-nu-latn", "pt-Latn-BR-u-nu-latn"),
+            ("ca-ES-valencia", "ca-Latn-ES-valencia"),
+            // Languages outside of the table are only canonicalized
+            ("tlh", "tlh"),
+            ("und-US", "und-US"),
+        ] {
+            assert_eq!(maximize(tag).as_deref(), Some(max), "{}", tag);
+        }
+        assert_eq!(maximize("en_US"), None);
+    }
+
+    #[test]
+    fn likely_scripts() {
+        for (tag, script) in [
+            ("zh", Some("Hans")),
+            ("zh-CN", Some("Hans")),
+            ("zh-SG", Some("Hans")),
+            ("zh-TW", Some("Hant")),
+            ("zh-HK", Some("Hant")),
+            ("zh-Hans-TW", Some("Hans")),
+            ("sr", Some("Cyrl")),
+            ("sr-RS", Some("Cyrl")),
+            ("sr-ME", Some("Latn")),
+            ("sr-Latn", Some("Latn")),
+            ("sr-Cyrl-ME", Some("Cyrl")),
+            ("en-US", Some("Latn")),
+            ("tlh", None),
+            ("und", None),
+        ] {
+            let locale = Locale::parse(tag).unwrap();
+            assert_eq!(locale.script_or_likely().as_deref(), script, "{}", tag);
+        }
+    }
+
+    #[test]
+    fn minimizes() {
+        for (tag, min) in [
+            ("en-Latn-US", "en"),
+            ("en-GB", "en-GB"),
+            ("en-Latn", "en"),
+            ("zh-Hans-CN", "zh"),
+            ("zh-Hant-TW", "zh-TW"),
+            ("zh-Hant", "zh-TW"),
+            ("zh-Hant-HK", "zh-HK"),
+            ("zh-Hans-SG", "zh-SG"),
+            ("sr-Latn-RS", "sr-Latn"),
+            ("sr-Latn-ME", "sr-ME"),
+            ("pt-BR-u-nu-latn", "pt-u-nu-latn"),
+            ("iw-IL", "he"),
+            ("tlh", "tlh"),
+        ] {
+            assert_eq!(minimize(tag).as_deref(), Some(min), "{}", tag);
+        }
+        assert_eq!(minimize("en_US"), None);
+
+        // Every entry of the table round trips
+        for (key, script, region) in LIKELY_SUBTAGS {
+            let max = Locale::parse(key).unwrap().maximize();
+            assert_eq!(max.script(), Some(*script), "{}", key);
+            assert_eq!(max.region(), Some(*region), "{}", key);
+            assert_eq!(max.minimize().maximize(), max, "{}", key);
+        }
+    }
+
+    #[test]
+    fn canonicalizes_owned() {
+        // Canonical tags are handed back without reallocating.
+        let tag = String::from("en-US");
+        let ptr = tag.as_ptr();
+        let canonical = canonicalize_owned(tag).unwrap();
+        assert_eq!(canonical, "en-US");
+        assert_eq!(canonical.as_ptr(), ptr);
+
+        for (tag, expected) in [
+            ("en-us", Ok("en-US")),
+            ("iw-IL", Ok("he-IL")),
+            ("en-UK", Ok("en-GB")),
+            ("zh-CHS", Ok("zh-Hans")),
+            ("en_US", Err("en_US")),
+        ] {
+            let canonical = canonicalize_owned(String::from(tag));
+            let canonical = canonical
+                .as_ref()
+                .map(String::as_str)
+                .map_err(String::as_str);
+            assert_eq!(canonical, expected, "{}", tag);
+            assert_eq!(canonicalize(tag).as_deref(), expected.ok(), "{}", tag);
+        }
+    }
+
+    #[test]
+    fn canonicalizes_aliases() {
+        for (tag, canonical) in [
+            ("iw-IL", "he-IL"),
+            ("in", "id"),
+            ("MO-md", "ro-MD"),
+            ("zh-CHS", "zh-Hans"),
+            ("zh-cht", "zh-Hant"),
+            ("en-uk", "en-GB"),
+            ("de-DD", "de-DE"),
+            ("i-klingon", "tlh"),
+            ("I-Navajo", "nv"),
+            ("zh-min-nan", "nan"),
+            ("en-GB-oed", "en-GB-oxendict"),
+            ("iw-Hebr-IL-u-ca-hebrew", "he-Hebr-IL-u-ca-hebrew"),
+        ] {
+            assert_eq!(canonicalize(tag).as_deref(), Some(canonical), "{}", tag);
+        }
+
+        // Subtags are only replaced in their own position
+        assert_eq!(canonicalize("en-x-iw").as_deref(), Some("en-x-iw"));
+        assert_eq!(canonicalize("zh-CHS-CN"), None);
+        assert_eq!(canonicalize("i-default"), None);
+
+        let equivalent = |a: &str, b: &str| {
+            Locale::parse(a)
+                .unwrap()
+                .canonical_eq(&Locale::parse(b).unwrap())
+        };
+        assert!(equivalent("iw-IL", "he-IL"));
+        assert!(equivalent("IN", "id"));
+        assert!(equivalent("mo-MD", "ro-md"));
+        assert!(equivalent("en-UK", "en-GB"));
+        assert!(!equivalent("he-IL", "yi-IL"));
+
+        // Legacy tags are compared through `canonicalize`
+        let zh_chs = Locale::parse(&canonicalize("zh-CHS").unwrap()).unwrap();
+        assert!(zh_chs.canonical_eq(&Locale::parse("zh-hans").unwrap()));
+        assert!(!zh_chs.canonical_eq(&Locale::parse("zh-Hant").unwrap()));
+
+        let locale = Locale::parse("jw-zr").unwrap();
+        assert_eq!(locale.canonical().to_string(), "jv-CD");
+        assert_eq!(locale.canonical().canonical(), locale.canonical());
+    }
+
+    #[test]
+    fn validates_and_canonicalizes() {
+        for (tag, canonical) in [
+            ("en", "en"),
+            ("EN-us", "en-US"),
+            ("sr-LATN-rs", "sr-Latn-RS"),
+            ("DE-de-1996-U-CO-phonebk", "de-DE-1996-u-co-phonebk"),
+            ("X-Klingon", "x-klingon"),
+        ] {
+            assert!(is_valid_bcp47(tag), "{} is invalid", tag);
+            assert_eq!(canonicalize(tag).as_deref(), Some(canonical));
+        }
+
+        for tag in [
+            "",
+            "en_US",
+            "en-",
+            "-en",
+            "en--US",
+            "en-US-x",
+            "de-1996-1996",
+        ] {
+            assert!(!is_valid_bcp47(tag), "{} is valid", tag);
+            assert_eq!(canonicalize(tag), None);
+        }
+    }
+
+    #[test]
+    fn canonical() {
+        for tag in [
+            "en",
+            "en-US",
+            "zh-Hant-TW",
+            "es-419",
+            "de-DE-1996-u-co-phonebk",
+            "sl-rozaj-biske",
+            "x-klingon",
+            "en-x-us",
+        ] {
+            assert!(is_canonical(tag), "{} isn't canonical", tag);
+            assert_eq!(Locale::parse(tag).unwrap().to_string(), tag);
+        }
+
+        for tag in [
+            "EN",
+            "en-us",
+            "zh-hant-TW",
+            "zh-HANT-TW",
+            "de-DE-U-co-phonebk",
+            "x-Klingon",
+        ] {
+            assert!(!is_canonical(tag), "{} is canonical", tag);
+            assert!(Locale::parse(tag).is_some());
+        }
+
+        for tag in [
+            "",
+            "en_US",
+            "-US",
+            "en-US-",
+            "sl-rozaj-rozaj",
+            "en-u-ca-u-nu",
+            "en-x",
+        ] {
+            assert!(!is_canonical(tag), "{} is canonical", tag);
+        }
+    }
+
+    #[test]
+    fn replaces_subtags() {
+        assert_eq!(set_region("en", "gb").as_deref(), Some("en-GB"));
+        assert_eq!(set_region("en-US", "GB").as_deref(), Some("en-GB"));
+        assert_eq!(
+            set_region("zh-hant-tw-u-ca-chinese", "HK").as_deref(),
+            Some("zh-Hant-HK-u-ca-chinese")
+        );
+        assert_eq!(
+            set_region("de-1996-x-custom", "AT").as_deref(),
+            Some("de-AT-1996-x-custom")
+        );
+        assert_eq!(set_region("en", "GBR"), None);
+        assert_eq!(set_region("en", "G1"), None);
+        assert_eq!(set_region("x-klingon", "US"), None);
+        assert_eq!(set_region("", "US"), None);
+
+        assert_eq!(set_script("zh", "HANS").as_deref(), Some("zh-Hans"));
+        assert_eq!(
+            set_script("sr-Cyrl-RS", "latn").as_deref(),
+            Some("sr-Latn-RS")
+        );
+        assert_eq!(set_script("zh", "Han"), None);
+        assert_eq!(set_script("zh_CN", "Hans"), None);
+    }
+
+    #[test]
+    fn posix() {
+        assert_eq!(to_posix("en-US"), "en_US.UTF-8");
+        assert_eq!(to_posix("fr"), "fr.UTF-8");
+        assert_eq!(to_posix("sr-Latn-RS"), "sr_RS.UTF-8@latin");
+        assert_eq!(to_posix("sr-latn"), "sr.UTF-8@latin");
+        assert_eq!(to_posix("sr-Cyrl-RS"), "sr_RS.UTF-8");
+        assert_eq!(to_posix("uz-Cyrl-UZ"), "uz_UZ.UTF-8@cyrillic");
+        assert_eq!(to_posix("ca-ES-valencia"), "ca_ES.UTF-8@valencia");
+        assert_eq!(to_posix("zh-Hant-TW"), "zh_TW.UTF-8");
+        assert_eq!(to_posix("ja-JP-u-ca-japanese-x-test"), "ja_JP.UTF-8");
+        assert_eq!(to_posix("es-419"), "es_419.UTF-8");
+        assert_eq!(to_posix("x-klingon"), ".UTF-8");
+        assert_eq!(to_posix("not a tag"), "not a tag.UTF-8");
+    }
+
+    #[test]
+    fn private_use() {
+        let locale = Locale::parse("en-US-u-ca-gregory-X-Custom-build").unwrap();
+        assert_eq!(locale.private_use(), Some("custom-build"));
+        assert_eq!(locale.extension('u'), Some("ca-gregory"));
+
+        let mut stripped = locale.clone();
+        assert!(stripped.strip_private_use());
+        assert_eq!(stripped.to_string(), "en-US-u-ca-gregory");
+        assert!(!stripped.strip_private_use());
+
+        assert_eq!(
+            Locale::parse("x-klingon").unwrap().private_use(),
+            Some("klingon")
+        );
+        assert_eq!(Locale::parse("en-US").unwrap().private_use(), None);
+    }
+
+    #[test]
+    fn typed_subtags() {
+        let locale = Locale::parse("es-419").unwrap();
+        assert_eq!(locale.language_typed().unwrap(), "es");
+        assert_eq!(locale.region_typed().unwrap(), "419");
+
+        assert_eq!(Locale::parse("en").unwrap().region_typed(), None);
+        assert_eq!(Locale::parse("x-klingon").unwrap().language_typed(), None);
+    }
+
+    #[test]
+    fn undetermined() {
+        assert!(Locale::parse(Locale::UND).unwrap().is_undetermined());
+        assert!(Locale::parse("und-Latn-US").unwrap().is_undetermined());
+        assert!(!Locale::parse("en").unwrap().is_undetermined());
+        assert!(!Locale::parse("x-und").unwrap().is_undetermined());
+        assert_eq!(Locale::parse("und").unwrap().fallbacks().count(), 0);
+    }
+}
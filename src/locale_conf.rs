@@ -0,0 +1,72 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// The variables assigned in a `locale.conf` file, like `LANG=de_DE.UTF-8`, as they're written.
+///
+/// These are the same variables as in the environment, so the Unix provider reads them the same
+/// way, including removing quotes.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Variables(BTreeMap<String, String>);
+
+impl Variables {
+    pub(crate) fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+}
+
+/// Reads `locale.conf` from the user's configuration directory.
+pub(crate) fn read_user() -> Option<Variables> {
+    read(&crate::unix::config_dir()?.join("locale.conf"))
+}
+
+/// Reads the system-wide `/etc/locale.conf`, as written by `localectl`.
+pub(crate) fn read_system() -> Option<Variables> {
+    read(Path::new("/etc/locale.conf"))
+}
+
+/// Returns [`None`] if the file doesn't exist or can't be read.
+fn read(path: &Path) -> Option<Variables> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|contents| parse(&contents))
+}
+
+/// Parses the contents of a `locale.conf` file, a list of shell-like `NAME=value` assignments.
+fn parse(contents: &str) -> Variables {
+    let mut variables = BTreeMap::new();
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        if let Some((name, value)) = line.split_once('=') {
+            variables.insert(String::from(name.trim()), String::from(value.trim()));
+        }
+    }
+
+    Variables(variables)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn parses_variables() {
+        let variables = parse(
+            "# Written by localectl\n\
+             LANG=de_DE.UTF-8\n\
+             \n\
+             export LC_TIME=\"en_GB.UTF-8\"\n\
+             LC_MESSAGES\n\
+             LANGUAGE = de:en \n",
+        );
+        assert_eq!(variables.get("LANG"), Some("de_DE.UTF-8"));
+        assert_eq!(variables.get("LC_TIME"), Some("\"en_GB.UTF-8\""));
+        assert_eq!(variables.get("LANGUAGE"), Some("de:en"));
+        assert_eq!(variables.get("LC_MESSAGES"), None);
+        assert_eq!(variables.get("LC_ALL"), None);
+
+        assert_eq!(parse(""), Default::default());
+    }
+}
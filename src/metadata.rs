@@ -0,0 +1,106 @@
+use std::string::String;
+
+/// The description of a locale from its `LC_IDENTIFICATION` category, as returned by
+/// [`get_locale_metadata`](crate::get_locale_metadata).
+///
+/// Every field is [`None`] if the locale doesn't fill it in, which is common for the `C` and
+/// `POSIX` locales.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LocaleMetadata {
+    /// The title of the locale, such as `English locale for the USA`.
+    pub title: Option<String>,
+    /// The name of the locale's language, such as `American English`.
+    pub language: Option<String>,
+    /// The name of the locale's territory, such as `United States`.
+    pub territory: Option<String>,
+}
+
+/// Reads the `LC_IDENTIFICATION` fields of the locale `name`, where an empty name selects the
+/// locale named by the environment.
+///
+/// The locale is loaded with `newlocale` on its own, so the locale of the process, which
+/// `setlocale` would change, is left alone. Returns [`None`] if the locale isn't installed.
+#[cfg(target_env = "gnu")]
+pub(crate) fn read(name: &str) -> Option<LocaleMetadata> {
+    use std::{
+        ffi::{CStr, CString},
+        os::raw::{c_char, c_int, c_void},
+    };
+
+    // `locale_t` is an opaque pointer and `nl_item` an `int`.
+    extern "C" {
+        fn newlocale(category_mask: c_int, locale: *const c_char, base: *mut c_void)
+            -> *mut c_void;
+        fn nl_langinfo_l(item: c_int, locale: *mut c_void) -> *const c_char;
+        fn freelocale(locale: *mut c_void);
+    }
+
+    // These are glibc's values, where the items of a category are numbered from the category's
+    // index shifted by 16 bits.
+    const LC_IDENTIFICATION: c_int = 12;
+    const LC_IDENTIFICATION_MASK: c_int = 1 << LC_IDENTIFICATION;
+    const IDENTIFICATION_TITLE: c_int = LC_IDENTIFICATION << 16;
+    const IDENTIFICATION_LANGUAGE: c_int = (LC_IDENTIFICATION << 16) | 7;
+    const IDENTIFICATION_TERRITORY: c_int = (LC_IDENTIFICATION << 16) | 8;
+
+    let name = CString::new(name).ok()?;
+
+    // SAFETY: `name` is a valid, NUL terminated string, and a null base creates a new locale
+    // object. The returned locale is owned by us.
+    let locale = unsafe { newlocale(LC_IDENTIFICATION_MASK, name.as_ptr(), core::ptr::null_mut()) };
+    if locale.is_null() {
+        return None;
+    }
+
+    let field = |item| {
+        // SAFETY: `locale` is a valid locale object and `item` is one of its items. The string
+        // is owned by the locale, which outlives its use here.
+        let value = unsafe { nl_langinfo_l(item, locale) };
+        if value.is_null() {
+            return None;
+        }
+        // SAFETY: `nl_langinfo_l` returns a NUL terminated string.
+        let value = unsafe { CStr::from_ptr(value) };
+        value
+            .to_str()
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(String::from)
+    };
+    let metadata = LocaleMetadata {
+        title: field(IDENTIFICATION_TITLE),
+        language: field(IDENTIFICATION_LANGUAGE),
+        territory: field(IDENTIFICATION_TERRITORY),
+    };
+
+    // SAFETY: `locale` was created by `newlocale` and isn't used after this.
+    unsafe { freelocale(locale) };
+
+    Some(metadata)
+}
+
+/// Other C libraries don't provide `LC_IDENTIFICATION`.
+#[cfg(not(target_env = "gnu"))]
+pub(crate) fn read(_name: &str) -> Option<LocaleMetadata> {
+    None
+}
+
+#[cfg(all(test, target_env = "gnu"))]
+mod tests {
+    use super::read;
+
+    #[test]
+    fn reads_identification() {
+        // The C locale is built into glibc, so it's always available.
+        let metadata = read("C").unwrap();
+        assert_eq!(
+            metadata.title.as_deref(),
+            Some("ISO/IEC 14652 i18n FDCC-set")
+        );
+        assert_eq!(metadata.language, None);
+
+        assert_eq!(read("xx_XX.UTF-8"), None);
+        assert_eq!(read("en\0US"), None);
+    }
+}
@@ -0,0 +1,67 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::{spin::SpinLock, Unique};
+
+static MOCK: SpinLock<Option<Vec<String>>> = SpinLock::new(None);
+
+/// Makes [`get_locales`](crate::get_locales) return `locales` on every platform, instead of
+/// querying the system.
+///
+/// This is meant for tests that need to run against specific locales without changing the
+/// state of the machine. Every function deriving its result from
+/// [`get_locales`](crate::get_locales), such as [`get_locale`](crate::get_locale) and
+/// [`negotiate`](crate::negotiate), sees the mocked list, and
+/// [`get_format_locale`](crate::get_format_locale) returns its first entry. Repeated tags are
+/// skipped, like for any other platform.
+///
+/// The mock is global, so tests that run in parallel will see each other's locales. The list
+/// cached by [`locales`](crate::locales) isn't updated until
+/// [`reset_locales`](crate::reset_locales) is called.
+///
+/// Only available with the `mock` feature, which shouldn't be enabled outside of
+/// `[dev-dependencies]`.
+///
+/// # Example
+///
+/// ```
+/// use sys_locale::{clear_mock_locales, get_locale, set_mock_locales};
+///
+/// set_mock_locales(vec![String::from("fr-CA"), String::from("en-US")]);
+/// assert_eq!(get_locale().as_deref(), Some("fr-CA"));
+///
+/// clear_mock_locales();
+/// ```
+pub fn set_mock_locales(locales: Vec<String>) {
+    MOCK.with(|mock| *mock = Some(locales));
+}
+
+/// Removes the locales set by [`set_mock_locales`], so that the system is queried again.
+pub fn clear_mock_locales() {
+    MOCK.with(|mock| *mock = None);
+}
+
+/// Returns the mocked locales, or [`None`] if no mock is set.
+fn get() -> Option<Vec<String>> {
+    MOCK.with(|mock| mock.clone())
+}
+
+/// Returns the mocked locales if a mock is set, or the ones from `provider` otherwise.
+///
+/// `provider` is only called when there's no mock, so the system isn't queried needlessly.
+pub(crate) fn locales_or<I: Iterator<Item = String>>(
+    provider: impl FnOnce() -> I,
+) -> impl Iterator<Item = String> {
+    let mocked = get();
+    let provided = match mocked {
+        Some(_) => None,
+        None => Some(provider()),
+    };
+
+    let mocked = Unique::new(mocked.into_iter().flatten());
+    mocked.chain(provided.into_iter().flatten())
+}
+
+/// Returns the first mocked locale, or [`None`] if no mock is set.
+pub(crate) fn format_locale() -> Option<Option<String>> {
+    get().map(|locales| locales.into_iter().next())
+}
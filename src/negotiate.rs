@@ -0,0 +1,270 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::{fallback_chain, matches_language};
+
+/// How well a tag matches a list of preferences, where lower is better.
+///
+/// The first field is the index of the preference that matched, so that matching a more
+/// preferred locale always wins. The second is how many subtags had to be dropped from either
+/// tag for them to match, so that an exact match wins over a fallback.
+type Rank = (usize, usize);
+
+/// Ranks `tag` against `preferences`, or returns [`None`] if it matches none of them.
+///
+/// A tag matches a preference if either one is in the [`fallback_chain`] of the other, so
+/// both `en` and `en-US-x-custom` match a preference for `en-US`, but `en-GB` doesn't.
+/// [Undetermined](crate::Locale::is_undetermined) tags never match.
+fn rank(tag: &str, preferences: &[Vec<String>]) -> Option<Rank> {
+    let chain = fallback_chain(tag);
+    if chain.is_empty() {
+        return None;
+    }
+
+    preferences.iter().enumerate().find_map(|(i, preference)| {
+        let broader = preference
+            .iter()
+            .position(|candidate| candidate.eq_ignore_ascii_case(tag));
+        let narrower = preference.first().and_then(|preferred| {
+            chain
+                .iter()
+                .position(|candidate| candidate.eq_ignore_ascii_case(preferred))
+        });
+
+        match (broader, narrower) {
+            (Some(a), Some(b)) => Some((i, a.min(b))),
+            (Some(distance), None) | (None, Some(distance)) => Some((i, distance)),
+            (None, None) => None,
+        }
+    })
+}
+
+fn chains(preferences: impl Iterator<Item = String>) -> Vec<Vec<String>> {
+    preferences.map(|p| fallback_chain(&p)).collect()
+}
+
+/// Picks the entry of `available` that best matches the user's preferred locales.
+///
+/// Preferences are considered in order, and the first one that matches any available tag
+/// wins. A tag matches a preference exactly, or through the [`fallback_chain`] of either of
+/// them: a preference for `fr-CA` matches an available `fr`, and a preference for `fr`
+/// matches an available `fr-FR`. Among matches for the same preference, an exact match is
+/// picked first, followed by the one with the fewest differing subtags. Ties go to the tag
+/// that appears first in `available`.
+///
+/// Fallbacks drop the region before the script, so tags with different scripts never match:
+/// a preference for `sr-Latn-RS` matches an available `sr-Latn`, but not `sr-Cyrl`.
+///
+/// Returns [`None`] if no available tag matches any preference.
+///
+/// # Example
+///
+/// ```no_run
+/// use sys_locale::negotiate;
+///
+/// let language = negotiate(&["en", "de", "fr"]).unwrap_or("en");
+/// println!("Showing the interface in {}", language);
+/// ```
+pub fn negotiate<'a>(available: &[&'a str]) -> Option<&'a str> {
+    negotiate_with(available, &chains(crate::get_locales()))
+}
+
+fn negotiate_with<'a>(available: &[&'a str], preferences: &[Vec<String>]) -> Option<&'a str> {
+    available
+        .iter()
+        .filter_map(|tag| rank(tag, preferences).map(|rank| (rank, *tag)))
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, tag)| tag)
+}
+
+/// Sorts `tags` by how well they match the user's preferred locales.
+///
+/// Tags matching a more preferred locale come first, and for the same preference, an exact
+/// match comes before one that only matches through a fallback, using the same rules as
+/// [`negotiate`]. Tags that don't match any preference are moved to the end. The sort is
+/// stable, so tags that match equally well keep their order.
+///
+/// # Example
+///
+/// ```no_run
+/// use sys_locale::sort_by_preference;
+///
+/// let mut stored = vec![String::from("de"), String::from("en-US"), String::from("fr")];
+/// sort_by_preference(&mut stored);
+/// println!("The stored languages, by preference: {:?}", stored);
+/// ```
+pub fn sort_by_preference(tags: &mut [String]) {
+    sort_with(tags, &chains(crate::get_locales()));
+}
+
+fn sort_with(tags: &mut [String], preferences: &[Vec<String>]) {
+    tags.sort_by_cached_key(|tag| rank(tag, preferences).unwrap_or((usize::MAX, usize::MAX)));
+}
+
+/// Returns the entries of `available` that match any of the user's preferred locales, ordered by
+/// preference.
+///
+/// This is the ordered intersection of the user's locales with the ones an application
+/// supports, such as for listing the user's languages in a settings screen. Tags match using
+/// the same fallback rules as [`negotiate`], which returns the first entry of this list, and
+/// tags that match no preference are left out. Each tag is returned as it appears in
+/// `available`, and tags that match equally well keep their relative order.
+///
+/// # Example
+///
+/// ```no_run
+/// const SUPPORTED: &[&str] = &["en", "de", "fr", "ja"];
+///
+/// // With preferences of `["fr-CA", "en-US"]`, this is `["fr", "en"]`.
+/// let languages = sys_locale::supported_locales(SUPPORTED);
+/// println!("Your languages: {:?}", languages);
+/// ```
+pub fn supported_locales(available: &[&str]) -> Vec<String> {
+    supported_with(available, &chains(crate::get_locales()))
+}
+
+fn supported_with(available: &[&str], preferences: &[Vec<String>]) -> Vec<String> {
+    let mut ranked: Vec<(Rank, &str)> = available
+        .iter()
+        .filter_map(|tag| rank(tag, preferences).map(|rank| (rank, *tag)))
+        .collect();
+    ranked.sort_by_key(|(rank, _)| *rank);
+
+    let mut supported: Vec<String> = Vec::with_capacity(ranked.len());
+    for (_, tag) in ranked {
+        if !supported.iter().any(|s| s == tag) {
+            supported.push(String::from(tag));
+        }
+    }
+    supported
+}
+
+/// Returns the index in [`get_locales`](crate::get_locales) of the first locale with the same
+/// language as `language`, ignoring region, script, and any other subtags.
+///
+/// This tells whether the user reads a language at all, and how much they prefer it: a user
+/// with `["fr-CA", "de-CH", "en"]` gets `Some(1)` for `de`. The comparison follows
+/// [`matches_language`], so `language` can also be a full tag like `de-DE`.
+///
+/// # Example
+///
+/// ```no_run
+/// match sys_locale::preferred_position("de") {
+///     Some(0) => println!("German is the preferred language"),
+///     Some(i) => println!("German is the preferred language #{}", i + 1),
+///     None => println!("German isn't a preferred language"),
+/// }
+/// ```
+pub fn preferred_position(language: &str) -> Option<usize> {
+    position_in(crate::get_locales(), language)
+}
+
+fn position_in(mut locales: impl Iterator<Item = String>, language: &str) -> Option<usize> {
+    locales.position(|locale| matches_language(&locale, language))
+}
+
+/// Returns `true` if any of the user's preferred locales has the same language as `language`.
+///
+/// This is shorthand for `preferred_position(language).is_some()`. See
+/// [`preferred_position`].
+pub fn prefers(language: &str) -> bool {
+    preferred_position(language).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chains, negotiate_with, position_in, sort_with, supported_with};
+    use alloc::{string::String, vec::Vec};
+
+    fn preferences(tags: &[&str]) -> Vec<Vec<String>> {
+        chains(tags.iter().map(|t| String::from(*t)))
+    }
+
+    #[test]
+    fn negotiates() {
+        let prefs = preferences(&["fr-CA", "en-US"]);
+        assert_eq!(negotiate_with(&["en", "fr"], &prefs), Some("fr"));
+        assert_eq!(
+            negotiate_with(&["en", "fr", "fr-CA"], &prefs),
+            Some("fr-CA")
+        );
+        assert_eq!(negotiate_with(&["en-US", "de"], &prefs), Some("en-US"));
+        assert_eq!(negotiate_with(&["de", "es"], &prefs), None);
+        assert_eq!(negotiate_with(&[], &prefs), None);
+
+        // A broader preference matches a more specific tag
+        let prefs = preferences(&["de"]);
+        assert_eq!(
+            negotiate_with(&["en", "de-AT", "de-DE"], &prefs),
+            Some("de-AT")
+        );
+
+        // Undetermined tags match nothing
+        let prefs = preferences(&["und", "en"]);
+        assert_eq!(negotiate_with(&["und", "en"], &prefs), Some("en"));
+    }
+
+    #[test]
+    fn keeps_scripts_apart() {
+        let available = ["sr-Cyrl", "sr-Latn"];
+        let prefs = preferences(&["sr-Latn-RS"]);
+        assert_eq!(negotiate_with(&available, &prefs), Some("sr-Latn"));
+        assert_eq!(supported_with(&available, &prefs), ["sr-Latn"]);
+
+        let prefs = preferences(&["sr-Cyrl-RS"]);
+        assert_eq!(negotiate_with(&available, &prefs), Some("sr-Cyrl"));
+        assert_eq!(supported_with(&available, &prefs), ["sr-Cyrl"]);
+
+        // The script is part of the POSIX name's modifier
+        let prefs = chains(crate::posix_to_bcp47("sr_RS@latin").into_iter());
+        assert_eq!(negotiate_with(&available, &prefs), Some("sr-Latn"));
+
+        // A matching region doesn't make up for a different script
+        assert_eq!(
+            negotiate_with(&["sr-Latn", "sr-Latn-ME"], &preferences(&["sr-Cyrl-ME"])),
+            None
+        );
+
+        let mut tags: Vec<String> = ["sr-Cyrl", "sr", "sr-Latn"]
+            .iter()
+            .map(|t| String::from(*t))
+            .collect();
+        sort_with(&mut tags, &preferences(&["sr-Latn-RS"]));
+        assert_eq!(tags, ["sr-Latn", "sr", "sr-Cyrl"]);
+    }
+
+    #[test]
+    fn intersects() {
+        let prefs = preferences(&["fr-CA", "en-US", "de"]);
+        assert_eq!(
+            supported_with(&["ja", "en", "de-AT", "fr", "fr-CA", "en"], &prefs),
+            ["fr-CA", "fr", "en", "de-AT"]
+        );
+        assert!(supported_with(&["ja", "es"], &prefs).is_empty());
+        assert!(supported_with(&[], &prefs).is_empty());
+        assert!(supported_with(&["en"], &[]).is_empty());
+    }
+
+    #[test]
+    fn finds_position() {
+        let position = |language: &str| {
+            let locales = ["fr-CA", "de-CH", "en", "de"];
+            position_in(locales.iter().map(|l| String::from(*l)), language)
+        };
+        assert_eq!(position("fr"), Some(0));
+        assert_eq!(position("de"), Some(1));
+        assert_eq!(position("en-GB"), Some(2));
+        assert_eq!(position("es"), None);
+        assert_eq!(position("und"), None);
+    }
+
+    #[test]
+    fn sorts() {
+        let prefs = preferences(&["fr-CA", "en-US"]);
+        let mut tags: Vec<String> = ["de", "en", "fr", "en-US", "fr-CA", "es", "FR"]
+            .iter()
+            .map(|t| String::from(*t))
+            .collect();
+        sort_with(&mut tags, &prefs);
+        assert_eq!(tags, ["fr-CA", "fr", "FR", "en-US", "en", "de", "es"]);
+    }
+}
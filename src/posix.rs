@@ -0,0 +1,259 @@
+use alloc::string::String;
+
+use crate::locale::{POSIX_SCRIPT_MODIFIERS, POSIX_VARIANT_MODIFIERS};
+
+/// A POSIX locale name split into its parts: `language[_territory][.codeset][@modifier]`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct PosixLocale<'a> {
+    /// The language and territory, such as `fr_FR`, without surrounding whitespace.
+    pub(crate) name: &'a str,
+    /// The codeset, such as `UTF-8`.
+    pub(crate) codeset: Option<&'a str>,
+    /// The modifier, such as `euro`.
+    pub(crate) modifier: Option<&'a str>,
+}
+
+impl<'a> PosixLocale<'a> {
+    /// Splits `locale` into its parts.
+    ///
+    /// Some tools put the modifier before the codeset, as in `fr_FR@euro.UTF-8`, so both parts
+    /// are recognized by their separator wherever they appear. Empty parts, like the codeset of
+    /// `en_US.`, are treated as missing.
+    pub(crate) fn parse(locale: &'a str) -> Self {
+        let is_separator = |c| c == '.' || c == '@';
+        let end = locale.find(is_separator).unwrap_or(locale.len());
+        let mut parsed = PosixLocale {
+            name: locale[..end].trim(),
+            codeset: None,
+            modifier: None,
+        };
+
+        let mut rest = &locale[end..];
+        while let Some(separator) = rest.chars().next() {
+            let part = &rest[1..];
+            let end = part.find(is_separator).unwrap_or(part.len());
+            let value = Some(part[..end].trim()).filter(|value| !value.is_empty());
+            match separator {
+                '.' => parsed.codeset = parsed.codeset.or(value),
+                _ => parsed.modifier = parsed.modifier.or(value),
+            }
+            rest = &part[end..];
+        }
+
+        parsed
+    }
+}
+
+/// Converts a POSIX locale string to a BCP 47 locale string.
+///
+/// This function processes the input `code` by removing any character encoding
+/// (the part after the `.` character) and any modifiers (the part after the `@` character).
+/// It replaces underscores (`_`) with hyphens (`-`) to conform to BCP 47 formatting.
+///
+/// Modifiers that glibc uses for a script or variant, like the `latin` in `sr_RS@latin` or the
+/// `valencia` in `ca_ES@valencia`, are converted to the matching subtag, the reverse of
+/// [`Locale::to_posix`](crate::Locale::to_posix). The modifier is found whether it comes before
+/// or after the codeset. Other modifiers, like `euro`, are dropped.
+///
+/// If the locale is already in the BCP 47 format, no changes are made.
+///
+/// Both a bare language like `fr` and a language with a territory like `fr_FR` are accepted,
+/// and become `fr` and `fr-FR`.
+///
+/// The `C` and `POSIX` locales, including forms with a codeset like `C.UTF-8`, don't name a
+/// language and return [`None`] instead, as does an empty or blank string.
+/// The same goes for a locale without a language, like the `_US` of a misconfigured `LANG`,
+/// which would otherwise become the invalid tag `-US`.
+///
+/// Whitespace around the name and separators left dangling at its end are removed, so that
+/// slightly malformed values like `en_US.`, `en_US @euro`, or `en_US_` still become `en-US`.
+/// Whitespace in place of the underscore, as in `en US`, is accepted too when the name is
+/// otherwise a two or three letter language and a two letter territory. Any other name
+/// containing whitespace returns [`None`], so the result never contains a space.
+///
+/// Useful links:
+/// - [The Open Group Base Specifications Issue 8 - 7. Locale](https://pubs.opengroup.org/onlinepubs/9799919799/basedefs/V1_chap07.html)
+/// - [The Open Group Base Specifications Issue 8 - 8. Environment Variables](https://pubs.opengroup.org/onlinepubs/9799919799/basedefs/V1_chap08.html)
+/// - [BCP 47 specification](https://www.ietf.org/rfc/bcp/bcp47.html)
+///
+/// # Examples
+///
+/// This doesn't access the system, so it can be used on any platform, such as for locale names
+/// read from a configuration file.
+///
+/// ```
+/// use sys_locale::posix_to_bcp47;
+///
+/// let bcp47 = posix_to_bcp47("en-US"); // already BCP 47
+/// assert_eq!(bcp47.as_deref(), Some("en-US")); // no changes
+///
+/// let bcp47 = posix_to_bcp47("fr"); // language only
+/// assert_eq!(bcp47.as_deref(), Some("fr"));
+///
+/// let bcp47 = posix_to_bcp47("en_US");
+/// assert_eq!(bcp47.as_deref(), Some("en-US"));
+///
+/// let bcp47 = posix_to_bcp47("ru_RU.UTF-8");
+/// assert_eq!(bcp47.as_deref(), Some("ru-RU"));
+///
+/// let bcp47 = posix_to_bcp47("fr_FR@dict");
+/// assert_eq!(bcp47.as_deref(), Some("fr-FR"));
+///
+/// let bcp47 = posix_to_bcp47("de_DE.UTF-8@euro");
+/// assert_eq!(bcp47.as_deref(), Some("de-DE"));
+///
+/// let bcp47 = posix_to_bcp47("sr_RS@latin.UTF-8");
+/// assert_eq!(bcp47.as_deref(), Some("sr-Latn-RS"));
+///
+/// let bcp47 = posix_to_bcp47("C.UTF-8");
+/// assert_eq!(bcp47, None);
+///
+/// let bcp47 = posix_to_bcp47("_US.UTF-8");
+/// assert_eq!(bcp47, None);
+///
+/// let bcp47 = posix_to_bcp47("en US.UTF-8");
+/// assert_eq!(bcp47.as_deref(), Some("en-US"));
+///
+/// let bcp47 = posix_to_bcp47("");
+/// assert_eq!(bcp47, None);
+/// ```
+pub fn posix_to_bcp47(locale: &str) -> Option<String> {
+    // TODO: Optimize to avoid creating a new buffer (see https://github.com/1Password/sys-locale/pull/33).
+    let parsed = PosixLocale::parse(locale);
+    let name = parsed.name;
+    if name.starts_with(|c| c == '_' || c == '-') {
+        return None;
+    }
+    let name = name.trim_end_matches(|c| c == '_' || c == '-');
+    if name.is_empty() || name == "C" || name == "POSIX" {
+        return None;
+    }
+
+    let mut bcp47: String = if name.contains(char::is_whitespace) {
+        separate_by_space(name)?
+    } else {
+        name.chars()
+            .map(|c| if c == '_' { '-' } else { c })
+            .collect()
+    };
+
+    let language = bcp47.split('-').next().unwrap_or_default();
+    let modifier = match parsed.modifier {
+        Some(modifier) => modifier,
+        None => return Some(bcp47),
+    };
+    let matches = |l: &str, m: &str| l.eq_ignore_ascii_case(language) && m == modifier;
+    if let Some((_, script, _)) = POSIX_SCRIPT_MODIFIERS
+        .iter()
+        .find(|(l, _, m)| matches(l, m))
+    {
+        let end = language.len();
+        bcp47.insert_str(end, script);
+        bcp47.insert(end, '-');
+    } else if let Some((_, variant, _)) = POSIX_VARIANT_MODIFIERS
+        .iter()
+        .find(|(l, _, m)| matches(l, m))
+    {
+        bcp47.push('-');
+        bcp47.push_str(variant);
+    }
+
+    Some(bcp47)
+}
+
+/// Converts a name whose language and territory are separated by whitespace instead of an
+/// underscore, like the `en US` some tools write, to `en-US`.
+///
+/// Only two alphabetic parts are accepted, so that names like `en US_x` or `New Zealand` that
+/// can't be told apart from free text return [`None`] instead of an invalid tag.
+fn separate_by_space(name: &str) -> Option<String> {
+    let mut parts = name.split_whitespace();
+    let (language, territory) = (parts.next()?, parts.next()?);
+    let is_subtag = |part: &str, len: core::ops::RangeInclusive<usize>| {
+        len.contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphabetic())
+    };
+    if parts.next().is_some() || !is_subtag(language, 2..=3) || !is_subtag(territory, 2..=2) {
+        return None;
+    }
+
+    let mut bcp47 = String::with_capacity(language.len() + 1 + territory.len());
+    bcp47.push_str(language);
+    bcp47.push('-');
+    bcp47.push_str(territory);
+    Some(bcp47)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{posix_to_bcp47, PosixLocale};
+
+    #[test]
+    fn parses_parts() {
+        let parts = |locale| {
+            let parsed = PosixLocale::parse(locale);
+            (parsed.name, parsed.codeset, parsed.modifier)
+        };
+
+        assert_eq!(parts("fr_FR"), ("fr_FR", None, None));
+        assert_eq!(
+            parts("fr_FR.UTF-8@euro"),
+            ("fr_FR", Some("UTF-8"), Some("euro"))
+        );
+        assert_eq!(
+            parts("fr_FR@euro.UTF-8"),
+            ("fr_FR", Some("UTF-8"), Some("euro"))
+        );
+        assert_eq!(parts("en_US @euro"), ("en_US", None, Some("euro")));
+        assert_eq!(parts("en_US.@"), ("en_US", None, None));
+        assert_eq!(parts(""), ("", None, None));
+    }
+
+    #[test]
+    fn converts_basic_forms() {
+        assert_eq!(posix_to_bcp47("fr").as_deref(), Some("fr"));
+        assert_eq!(posix_to_bcp47("fr_FR").as_deref(), Some("fr-FR"));
+        assert_eq!(posix_to_bcp47("fr.UTF-8").as_deref(), Some("fr"));
+        assert_eq!(posix_to_bcp47("C"), None);
+        assert_eq!(posix_to_bcp47("POSIX"), None);
+        assert_eq!(posix_to_bcp47(""), None);
+        assert_eq!(posix_to_bcp47(" "), None);
+        assert_eq!(posix_to_bcp47(".UTF-8"), None);
+        assert_eq!(posix_to_bcp47("@euro"), None);
+    }
+
+    #[test]
+    fn converts_modifiers() {
+        for (posix, bcp47) in [
+            ("fr_FR@euro.UTF-8", "fr-FR"),
+            ("fr_FR.UTF-8@euro", "fr-FR"),
+            ("sr_RS.UTF-8@latin", "sr-Latn-RS"),
+            ("sr_RS@latin.UTF-8", "sr-Latn-RS"),
+            ("sr@latin", "sr-Latn"),
+            ("uz_UZ.UTF-8@cyrillic", "uz-Cyrl-UZ"),
+            ("ca_ES.UTF-8@valencia", "ca-ES-valencia"),
+            ("ca_ES@valencia.UTF-8", "ca-ES-valencia"),
+            // Only the modifiers glibc uses for the language are converted
+            ("de_DE@latin", "de-DE"),
+            ("sr_RS@Latin", "sr-RS"),
+        ] {
+            assert_eq!(posix_to_bcp47(posix).as_deref(), Some(bcp47), "{}", posix);
+        }
+    }
+
+    #[test]
+    fn separates_by_space() {
+        for (posix, bcp47) in [
+            ("en US", Some("en-US")),
+            ("en  US.UTF-8", Some("en-US")),
+            ("fil\tPH", Some("fil-PH")),
+            ("sr RS@latin", Some("sr-Latn-RS")),
+            ("en US_x", None),
+            ("en_GB US", None),
+            ("New Zealand", None),
+            ("en US GB", None),
+            ("e n", None),
+        ] {
+            assert_eq!(posix_to_bcp47(posix).as_deref(), bcp47, "{}", posix);
+        }
+    }
+}
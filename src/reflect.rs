@@ -0,0 +1,9 @@
+use js_sys::Reflect;
+use wasm_bindgen::JsValue;
+
+/// Looks up `key` on `target`, treating both a missing and an `undefined` property as absent.
+pub(crate) fn property(target: &JsValue, key: &str) -> Option<JsValue> {
+    Reflect::get(target, &JsValue::from_str(key))
+        .ok()
+        .filter(|value| !value.is_undefined() && !value.is_null())
+}
@@ -0,0 +1,502 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::Locale;
+
+/// Configurable locale resolution, for when the defaults of [`get_locales`](crate::get_locales)
+/// don't fit.
+///
+/// By default, no option changes the tags, so `Resolver::new().get_locales()` returns exactly
+/// what [`get_locales`](crate::get_locales) does.
+///
+/// # Example
+///
+/// ```no_run
+/// use sys_locale::Resolver;
+///
+/// let locale = Resolver::new().qualify_neutral(true).get_locale();
+/// println!("The current locale is {:?}", locale);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Resolver {
+    qualify_neutral: bool,
+    canonicalize: bool,
+    prefer_specific: bool,
+    strip_private_use: bool,
+    default_locale: Option<String>,
+    excluded: Vec<String>,
+    #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+    override_file: Option<std::path::PathBuf>,
+    #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+    languages_file: Option<std::path::PathBuf>,
+}
+
+impl Resolver {
+    /// Creates a resolver with the default options, which leave tags unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the user's region to preferred locales that don't specify one.
+    ///
+    /// Windows reports a neutral language like `en` or `zh-Hans` when that's what the user
+    /// added to their display languages, even if they configured a region elsewhere. With this
+    /// enabled, such tags are completed with the region of the
+    /// [format locale](crate::get_format_locale), so `en` becomes `en-GB` for a user with British
+    /// regional formats. On Windows, that's the locale from `GetUserDefaultLocaleName`. Tags with
+    /// only a script are qualified the same way, so "Chinese (Simplified)", reported as
+    /// `zh-Hans`, becomes `zh-Hans-CN` for a user with Chinese regional formats. The same applies
+    /// on other platforms that report a format locale.
+    ///
+    /// Tags that already have a region, or that aren't well-formed, are left untouched. This is
+    /// disabled by default, since a neutral tag is valid BCP 47 and may be what the user chose.
+    pub fn qualify_neutral(mut self, enabled: bool) -> Self {
+        self.qualify_neutral = enabled;
+        self
+    }
+
+    /// Replaces every tag with its [canonical form](crate::canonicalize).
+    ///
+    /// Tags are already well-formed on most systems, but some still report deprecated or legacy
+    /// forms, like `iw` for Hebrew or `zh-CHS` on older Windows. With this enabled, such tags are
+    /// normalized before any other option is applied, and tags that become duplicates of
+    /// earlier ones are dropped. Tags that aren't well-formed are left untouched. This is
+    /// disabled by default.
+    pub fn canonicalize(mut self, enabled: bool) -> Self {
+        self.canonicalize = enabled;
+        self
+    }
+
+    /// Moves the most specific of the leading tags that are fallbacks of one another to the
+    /// front.
+    ///
+    /// Some systems list a bare language ahead of a more specific tag for the same language,
+    /// like macOS reporting `["en", "en-US", "fr"]` in some configurations. The first tag is
+    /// what most applications use, so `en` would win even though the user also named the
+    /// region. With this enabled, the first tags are considered as long as each is in the
+    /// [fallback chain](crate::fallback_chain) of the next one or the other way around, and the
+    /// most specific of them is moved to the front, giving `["en-US", "en", "fr"]`. The other
+    /// tags keep their order, and nothing changes after the first unrelated tag, so
+    /// `["en", "fr", "en-US"]` is returned as it is.
+    ///
+    /// This is disabled by default, since it changes the order the platform reported, which
+    /// may be what the user chose.
+    pub fn prefer_specific(mut self, enabled: bool) -> Self {
+        self.prefer_specific = enabled;
+        self
+    }
+
+    /// Sets whether private use sequences (`-x-...`) are kept in the returned tags.
+    ///
+    /// Private use subtags like the `x-custom` in `en-US-x-custom` are only meaningful to
+    /// whoever added them, and stricter consumers may reject them. With this disabled, the
+    /// sequence is removed, along with tags that consist of nothing else, such as `x-klingon`.
+    /// Extensions preceding the sequence are kept, so `en-u-ca-buddhist-x-custom` becomes
+    /// `en-u-ca-buddhist`.
+    ///
+    /// Tags that aren't well-formed are left untouched. Private use subtags are kept by default.
+    pub fn keep_private_use(mut self, keep: bool) -> Self {
+        self.strip_private_use = !keep;
+        self
+    }
+
+    /// Sets the tag [`primary_locale`](Self::primary_locale) returns when no preferred locale is
+    /// valid, instead of [`DEFAULT_LOCALE`](crate::DEFAULT_LOCALE).
+    ///
+    /// The tag is returned as given, so it should be a valid BCP 47 tag itself.
+    pub fn default_locale(mut self, tag: impl Into<String>) -> Self {
+        self.default_locale = Some(tag.into());
+        self
+    }
+
+    /// Ignores the environment variable `variable` when looking up the preferred locales.
+    ///
+    /// On Unix, the preferred locales come from `LANGUAGE`, `LC_ALL`, `LC_MESSAGES`, and `LANG`.
+    /// Some setups point `LANGUAGE` at a message catalog rather than a list of locales, and
+    /// sandboxed applications may not want to trust it, or `LC_ALL`, at all. Excluding a
+    /// variable skips it as if it were unset, while the others are still consulted in their
+    /// usual order. This can be called repeatedly to exclude several variables.
+    ///
+    /// Names are matched exactly, so `"language"` doesn't exclude `LANGUAGE`. This has no effect
+    /// on other platforms, which don't read the preferred locales from the environment.
+    ///
+    /// ```no_run
+    /// use sys_locale::Resolver;
+    ///
+    /// let locale = Resolver::new().without("LANGUAGE").get_locale();
+    /// ```
+    pub fn without(mut self, variable: impl Into<String>) -> Self {
+        let variable = variable.into();
+        if !self.excluded.contains(&variable) {
+            self.excluded.push(variable);
+        }
+        self
+    }
+
+    /// Reads a locale from the file at `path` and puts it ahead of the ones reported by the
+    /// system.
+    ///
+    /// This lets operators set an application's locale by managing a file, such as
+    /// `/etc/myapp/locale`, in deployments where the environment is fixed by an orchestrator.
+    /// The file must contain a single BCP 47 tag, optionally followed by a newline. It's read
+    /// on every lookup, and ignored if it doesn't exist, can't be read, or doesn't hold a
+    /// well-formed tag, in which case only the system's locales are returned.
+    ///
+    /// No file is read unless this is called, and the path is never guessed. Only available on
+    /// Linux, BSD, and other Unix platforms where this crate uses the standard library.
+    ///
+    /// ```no_run
+    /// use sys_locale::Resolver;
+    ///
+    /// let locale = Resolver::new().override_file("/etc/myapp/locale").get_locale();
+    /// ```
+    #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+    pub fn override_file(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.override_file = Some(path.into());
+        self
+    }
+
+    /// Reads an ordered list of locales from the file at `path` and puts them ahead of the ones
+    /// reported by the system.
+    ///
+    /// This is for applications that keep their own "preferred languages" setting, so that it
+    /// goes through the same options as the system's locales instead of bypassing them. The
+    /// file lists BCP 47 tags separated by newlines or colons, like `LANGUAGE` does, with the
+    /// most preferred first. Whitespace around tags is ignored, and entries that aren't
+    /// well-formed tags are skipped. The file is read on every lookup, and ignored if it
+    /// doesn't exist or can't be read.
+    ///
+    /// A tag from an [override file](Self::override_file) still comes before these. Only
+    /// available on Linux, BSD, and other Unix platforms where this crate uses the standard
+    /// library.
+    ///
+    /// ```no_run
+    /// use sys_locale::Resolver;
+    ///
+    /// // With a file containing "fr-CA\nfr\nen", this is `Some("fr-CA")`.
+    /// let locale = Resolver::new().languages_file("/home/user/.myapp/languages").get_locale();
+    /// ```
+    #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+    pub fn languages_file(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.languages_file = Some(path.into());
+        self
+    }
+
+    /// Returns the most preferred locale after applying the configured options.
+    ///
+    /// This is the first entry of [`get_locales`](Self::get_locales).
+    pub fn get_locale(&self) -> Option<String> {
+        self.get_locales().next()
+    }
+
+    /// Returns the first valid tag of [`get_locales`](Self::get_locales), or the
+    /// [default locale](Self::default_locale) if there is none.
+    ///
+    /// See [`primary_locale`](crate::primary_locale) for which tags are considered valid.
+    pub fn primary_locale(&self) -> String {
+        crate::first_valid(self.get_locales()).unwrap_or_else(|| match &self.default_locale {
+            Some(tag) => tag.clone(),
+            None => String::from(crate::DEFAULT_LOCALE),
+        })
+    }
+
+    /// Returns the preferred locales after applying the configured options, in descending order
+    /// of preference.
+    pub fn get_locales(&self) -> impl Iterator<Item = String> {
+        let mut locales: Vec<String> = self.provider_locales();
+
+        #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+        if let Some(path) = self.languages_file.as_deref() {
+            let mut listed = read_languages(path);
+            for locale in locales {
+                if !listed.contains(&locale) {
+                    listed.push(locale);
+                }
+            }
+            locales = listed;
+        }
+
+        if self.canonicalize {
+            locales = canonicalize(locales);
+        }
+
+        if self.prefer_specific {
+            prefer_specific(&mut locales);
+        }
+
+        #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+        if let Some(tag) = self.override_file.as_deref().and_then(read_override) {
+            locales.retain(|locale| *locale != tag);
+            locales.insert(0, tag);
+        }
+
+        if self.qualify_neutral {
+            let format = crate::get_format_locale().and_then(|l| Locale::parse(&l));
+            if let Some(region) = format.as_ref().and_then(Locale::region) {
+                locales = qualify_neutral(locales, region);
+            }
+        }
+
+        if self.strip_private_use {
+            locales = strip_private_use(locales);
+        }
+
+        locales.into_iter()
+    }
+
+    /// Returns the locales reported by the platform, skipping any [excluded](Self::without)
+    /// variables.
+    fn provider_locales(&self) -> Vec<String> {
+        #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+        if !self.excluded.is_empty() {
+            let excluded: Vec<&str> = self.excluded.iter().map(String::as_str).collect();
+            return crate::locales_from(|| crate::unix::get_without(&excluded)).collect();
+        }
+
+        crate::get_locales().collect()
+    }
+}
+
+/// Reads the locale from an [override file](Resolver::override_file), if it holds a well-formed
+/// tag.
+#[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+fn read_override(path: &std::path::Path) -> Option<String> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let tag = contents.trim();
+    Some(String::from(tag)).filter(|_| crate::is_valid_bcp47(tag))
+}
+
+/// Reads the locales from a [languages file](Resolver::languages_file), or none if it can't be
+/// read.
+#[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+fn read_languages(path: &std::path::Path) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .map(|contents| parse_languages(&contents))
+        .unwrap_or_default()
+}
+
+/// Parses a list of tags separated by newlines or colons, skipping repeated entries and those
+/// that aren't well-formed.
+#[cfg(any(
+    all(unix, not(any(target_vendor = "apple", target_os = "android"))),
+    test
+))]
+fn parse_languages(contents: &str) -> Vec<String> {
+    let mut languages: Vec<String> = Vec::new();
+    for tag in contents.split(|c| c == '\n' || c == ':').map(str::trim) {
+        if crate::is_valid_bcp47(tag) && !languages.iter().any(|l| l == tag) {
+            languages.push(String::from(tag));
+        }
+    }
+    languages
+}
+
+/// Replaces every tag in `locales` with its canonical form, dropping any tags that become
+/// duplicates of earlier ones.
+fn canonicalize(locales: Vec<String>) -> Vec<String> {
+    let mut canonical: Vec<String> = Vec::with_capacity(locales.len());
+    for tag in locales {
+        let tag = crate::locale::canonicalize_owned(tag).unwrap_or_else(|tag| tag);
+        if !canonical.contains(&tag) {
+            canonical.push(tag);
+        }
+    }
+    canonical
+}
+
+/// Moves the most specific tag of the leading run of tags that are fallbacks of one another to
+/// the front of `locales`. The first of equally specific tags wins.
+fn prefer_specific(locales: &mut [String]) {
+    if locales.is_empty() {
+        return;
+    }
+    let chains: Vec<Vec<String>> = locales
+        .iter()
+        .map(|tag| crate::fallback_chain(tag))
+        .collect();
+    let related = |a: usize, b: usize| {
+        let contains =
+            |chain: &[String], tag: &str| chain.iter().any(|other| other.eq_ignore_ascii_case(tag));
+        contains(&chains[a], &locales[b]) || contains(&chains[b], &locales[a])
+    };
+
+    let mut end = 1;
+    while end < locales.len() && related(end - 1, end) {
+        end += 1;
+    }
+
+    let mut most_specific = 0;
+    for i in 1..end {
+        if chains[i].len() > chains[most_specific].len() {
+            most_specific = i;
+        }
+    }
+    locales[..=most_specific].rotate_right(1);
+}
+
+/// Adds `region` to every well-formed tag in `locales` without a region, dropping any tags
+/// that become duplicates of earlier ones.
+fn qualify_neutral(locales: Vec<String>, region: &str) -> Vec<String> {
+    let mut qualified: Vec<String> = Vec::with_capacity(locales.len());
+    for tag in locales {
+        let tag = match Locale::parse(&tag) {
+            Some(locale) if locale.region().is_none() => match locale.with_region(region) {
+                Some(qualified) => qualified.to_string(),
+                None => tag,
+            },
+            _ => tag,
+        };
+
+        if !qualified.contains(&tag) {
+            qualified.push(tag);
+        }
+    }
+    qualified
+}
+
+/// Removes the private use sequence from every well-formed tag in `locales`, dropping tags
+/// that are left empty or become duplicates of earlier ones.
+fn strip_private_use(locales: Vec<String>) -> Vec<String> {
+    let mut stripped: Vec<String> = Vec::with_capacity(locales.len());
+    for tag in locales {
+        let tag = match Locale::parse(&tag) {
+            Some(mut locale) if locale.private_use().is_some() => {
+                locale.strip_private_use();
+                locale.to_string()
+            }
+            _ => tag,
+        };
+
+        if !tag.is_empty() && !stripped.contains(&tag) {
+            stripped.push(tag);
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        canonicalize, parse_languages, prefer_specific, qualify_neutral, strip_private_use,
+    };
+    use alloc::{string::String, vec::Vec};
+
+    fn qualify(locales: &[&str], region: &str) -> Vec<String> {
+        qualify_neutral(locales.iter().map(|l| String::from(*l)).collect(), region)
+    }
+
+    #[test]
+    fn qualifies_neutral_tags() {
+        assert_eq!(qualify(&["en"], "GB"), ["en-GB"]);
+        assert_eq!(qualify(&["en-US", "fr"], "CA"), ["en-US", "fr-CA"]);
+        assert_eq!(qualify(&["en", "en-GB"], "GB"), ["en-GB"]);
+        assert_eq!(qualify(&["sr-Latn"], "RS"), ["sr-Latn-RS"]);
+        assert_eq!(qualify(&["zh-Hans"], "CN"), ["zh-Hans-CN"]);
+        assert_eq!(qualify(&["zh-Hant", "en"], "TW"), ["zh-Hant-TW", "en-TW"]);
+        assert_eq!(qualify(&["zh-Hans-SG"], "CN"), ["zh-Hans-SG"]);
+        assert_eq!(
+            qualify(&["x-private", "en_US"], "GB"),
+            ["x-private", "en_US"]
+        );
+    }
+
+    #[test]
+    #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+    fn reads_override() {
+        use super::read_override;
+        use std::fs;
+
+        let path = std::env::temp_dir().join(format!("sys-locale-override-{}", std::process::id()));
+        let read = |contents: &str| {
+            fs::write(&path, contents).unwrap();
+            read_override(&path)
+        };
+
+        assert_eq!(read("de-AT").as_deref(), Some("de-AT"));
+        assert_eq!(read("  fr-CA\n").as_deref(), Some("fr-CA"));
+        assert_eq!(read("en_US.UTF-8"), None);
+        assert_eq!(read("en-US\nfr-FR\n"), None);
+        assert_eq!(read(""), None);
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read_override(&path), None);
+    }
+
+    #[test]
+    fn parses_languages() {
+        assert_eq!(parse_languages("fr-CA\nfr\nen\n"), ["fr-CA", "fr", "en"]);
+        assert_eq!(parse_languages("de-AT:de:en"), ["de-AT", "de", "en"]);
+        assert_eq!(
+            parse_languages(" ja \r\n\nen_US:ja\n en-GB:"),
+            ["ja", "en-GB"]
+        );
+        assert!(parse_languages("").is_empty());
+    }
+
+    #[test]
+    #[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+    fn reads_languages() {
+        use super::{read_languages, Resolver};
+        use std::fs;
+
+        let path =
+            std::env::temp_dir().join(format!("sys-locale-languages-{}", std::process::id()));
+        fs::write(&path, "x-first\nfr-CA:fr\n").unwrap();
+        let locales: Vec<String> = Resolver::new()
+            .languages_file(&path)
+            .get_locales()
+            .collect();
+        assert_eq!(locales[..3], ["x-first", "fr-CA", "fr"]);
+
+        fs::remove_file(&path).unwrap();
+        assert!(read_languages(&path).is_empty());
+    }
+
+    #[test]
+    fn canonicalizes() {
+        let canonical =
+            |locales: &[&str]| canonicalize(locales.iter().map(|l| String::from(*l)).collect());
+        assert_eq!(
+            canonical(&["iw-IL", "zh-CHS", "EN-us"]),
+            ["he-IL", "zh-Hans", "en-US"]
+        );
+        assert_eq!(canonical(&["he-IL", "iw-il", "en_US"]), ["he-IL", "en_US"]);
+    }
+
+    #[test]
+    fn prefers_specific() {
+        let prefer = |locales: &[&str]| {
+            let mut locales: Vec<String> = locales.iter().map(|l| String::from(*l)).collect();
+            prefer_specific(&mut locales);
+            locales
+        };
+        assert_eq!(prefer(&["en", "en-US", "fr"]), ["en-US", "en", "fr"]);
+        assert_eq!(prefer(&["en", "en-US", "en-GB"]), ["en-US", "en", "en-GB"]);
+        assert_eq!(
+            prefer(&["zh", "zh-Hant", "zh-Hant-TW"]),
+            ["zh-Hant-TW", "zh", "zh-Hant"]
+        );
+        assert_eq!(prefer(&["en-US", "en"]), ["en-US", "en"]);
+        assert_eq!(prefer(&["en", "fr", "en-US"]), ["en", "fr", "en-US"]);
+        assert_eq!(prefer(&["und", "en"]), ["und", "en"]);
+        assert_eq!(prefer(&["en"]), ["en"]);
+        assert!(prefer(&[]).is_empty());
+    }
+
+    #[test]
+    fn strips_private_use() {
+        let strip = |locales: &[&str]| {
+            strip_private_use(locales.iter().map(|l| String::from(*l)).collect())
+        };
+        assert_eq!(strip(&["en-US-x-custom", "fr"]), ["en-US", "fr"]);
+        assert_eq!(
+            strip(&["ja-JP-u-ca-japanese-x-test"]),
+            ["ja-JP-u-ca-japanese"]
+        );
+        assert_eq!(strip(&["x-klingon", "en"]), ["en"]);
+        assert_eq!(strip(&["en-x-a", "en"]), ["en"]);
+        assert_eq!(strip(&["en_US-x-a"]), ["en_US-x-a"]);
+    }
+}
@@ -0,0 +1,43 @@
+use core::{
+    cell::UnsafeCell,
+    hint,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// A minimal spin lock, for state shared between threads on targets where `std::sync::Mutex`
+/// isn't available.
+///
+/// It's only meant to guard short, non-blocking accesses to small values.
+pub(crate) struct SpinLock<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// SAFETY: `value` is only accessed while `locked` is held, so it's never accessed from two
+// threads at once.
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Runs `f` with exclusive access to the value.
+    pub(crate) fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            hint::spin_loop();
+        }
+
+        // SAFETY: The lock is held, so nothing else can access the value until it's released.
+        let result = f(unsafe { &mut *self.value.get() });
+        self.locked.store(false, Ordering::Release);
+        result
+    }
+}
@@ -0,0 +1,151 @@
+use alloc::string::String;
+use core::fmt;
+
+use crate::locale::{is_language, is_region};
+
+/// A validated primary language subtag, such as `en` or `zh`, in lowercase.
+///
+/// Comparisons with strings ignore case, like BCP 47 itself, so `Language` values can be
+/// matched against literals without normalizing them first.
+///
+/// # Example
+///
+/// ```
+/// use sys_locale::Language;
+///
+/// let language = Language::new("EN").unwrap();
+/// assert_eq!(language, "en");
+/// assert_eq!(language.to_string(), "en");
+/// assert!(Language::new("US1").is_none());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Language(String);
+
+impl Language {
+    /// Validates `subtag` as a language subtag: two, three, or five to eight letters.
+    ///
+    /// Returns [`None`] if it isn't one.
+    pub fn new(subtag: &str) -> Option<Language> {
+        Some(Language(subtag.to_ascii_lowercase())).filter(|_| is_language(subtag))
+    }
+
+    /// Returns the subtag as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A validated region subtag, either two letters (`US`) or three digits (`419`), in uppercase.
+///
+/// Comparisons with strings ignore case, like BCP 47 itself.
+///
+/// # Example
+///
+/// ```
+/// use sys_locale::Region;
+///
+/// let region = Region::new("gb").unwrap();
+/// assert_eq!(region, "GB");
+/// assert!(Region::new("en").is_some());
+/// assert!(Region::new("eng").is_none());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Region(String);
+
+impl Region {
+    /// Validates `subtag` as a region subtag: two letters or three digits.
+    ///
+    /// Returns [`None`] if it isn't one.
+    pub fn new(subtag: &str) -> Option<Region> {
+        Some(Region(subtag.to_ascii_uppercase())).filter(|_| is_region(subtag))
+    }
+
+    /// Returns `true` if this is a two letter region, which is also an ISO 3166-1 country code.
+    ///
+    /// Three digit regions are UN M.49 areas like `419` (Latin America), which span several
+    /// countries.
+    pub fn is_country(&self) -> bool {
+        self.0.len() == 2
+    }
+
+    /// Returns the subtag as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+macro_rules! impl_subtag {
+    ($name:ident) => {
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0.eq_ignore_ascii_case(other)
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0.eq_ignore_ascii_case(other)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(subtag: $name) -> String {
+                subtag.0
+            }
+        }
+    };
+}
+
+impl_subtag!(Language);
+impl_subtag!(Region);
+
+#[cfg(test)]
+mod tests {
+    use super::{Language, Region};
+    use alloc::string::{String, ToString};
+
+    #[test]
+    fn languages() {
+        let language = Language::new("Zh").unwrap();
+        assert_eq!(language.as_str(), "zh");
+        assert_eq!(language, "ZH");
+        assert_eq!(language, *"zh");
+        assert_eq!(String::from(language), "zh");
+
+        assert!(Language::new("haw").is_some());
+        assert!(Language::new("klingon").is_some());
+        assert!(Language::new("e").is_none());
+        assert!(Language::new("engl").is_none());
+        assert!(Language::new("e1").is_none());
+        assert!(Language::new("").is_none());
+    }
+
+    #[test]
+    fn regions() {
+        let region = Region::new("us").unwrap();
+        assert_eq!(region.to_string(), "US");
+        assert_eq!(region, "us");
+        assert!(region.is_country());
+
+        let region = Region::new("419").unwrap();
+        assert_eq!(region.as_ref(), "419");
+        assert!(!region.is_country());
+
+        assert!(Region::new("U").is_none());
+        assert!(Region::new("USA").is_none());
+        assert!(Region::new("41").is_none());
+        assert!(Region::new("").is_none());
+    }
+}
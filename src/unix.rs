@@ -1,17 +1,29 @@
-use std::{env, ffi::OsStr};
-
-const LANGUAGE: &str = "LANGUAGE";
-const LC_ALL: &str = "LC_ALL";
-const LC_MESSAGES: &str = "LC_MESSAGES";
-const LANG: &str = "LANG";
-
-/// Environment variable access abstraction to allow testing without
-/// mutating env variables.
-///
-/// Use [StdEnv] to query [std::env]
-trait EnvAccess {
-    /// See also [std::env::var]
-    fn get(&self, key: impl AsRef<OsStr>) -> Option<String>;
+#[cfg(all(any(feature = "kde", feature = "locale-conf"), not(feature = "strict")))]
+use std::fmt::Write;
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    ffi::OsStr,
+    fmt, fs,
+};
+
+use crate::{
+    unix_env::{
+        _explain, _get, _get_categories, _get_format, _get_regional, _get_timezone, zone_id,
+        EnvAccess,
+    },
+    Category, LocaleList,
+};
+
+/// The variables of a `locale.conf` file, which stand in for the environment
+#[cfg(all(feature = "locale-conf", not(feature = "strict")))]
+impl EnvAccess for crate::locale_conf::Variables {
+    fn get(&self, key: impl AsRef<OsStr>) -> Option<String> {
+        key.as_ref()
+            .to_str()
+            .and_then(|key| self.get(key))
+            .map(String::from)
+    }
 }
 
 /// Proxy to [std::env]
@@ -22,275 +34,159 @@ impl EnvAccess for StdEnv {
     }
 }
 
-pub(crate) fn get() -> impl Iterator<Item = String> {
-    _get(&StdEnv)
+pub(crate) const SUPPORTED: bool = true;
+
+pub(crate) type Locales = std::vec::IntoIter<String>;
+
+pub(crate) fn get() -> LocaleList {
+    get_without(&[])
 }
 
-/// Retrieves a list of unique locales by checking specific environment variables
-/// in a predefined order: LANGUAGE, LC_ALL, LC_MESSAGES, and LANG.
-///
-/// The function first checks the `LANGUAGE` environment variable, which can contain
-/// one or more locales separated by a colon (`:`). It then splits these values,
-/// converts them from [POSIX](https://pubs.opengroup.org/onlinepubs/9799919799/basedefs/V1_chap08.html)
-/// to [BCP 47](https://www.ietf.org/rfc/bcp/bcp47.html) format, and adds them to the list of locales
-/// if they are not already included.
-///
-/// Next, the function checks the `LC_ALL`, `LC_MESSAGES`, and `LANG` environment
-/// variables. Each of these variables contains a single locale. If a locale is found,
-/// and it's not empty, it is converted to BCP 47 format and added to the list if
-/// it is not already included.
-///
-/// For more information check this issue: https://github.com/1Password/sys-locale/issues/14.
-///
-/// The function ensures that locales are returned in the order of precedence
-/// and without duplicates. The final list of locales is returned as an iterator.
-///
-/// # Returns
-///
-/// An iterator over the unique locales found in the environment variables.
-///
-/// # Environment Variables Checked
-///
-/// 1. `LANGUAGE` - Can contain multiple locales, each separated by a colon (`:`), highest priority.
-/// 2. `LC_ALL` - Contains a single locale, high priority.
-/// 3. `LC_MESSAGES` - Contains a single locale, medium priority.
-/// 4. `LANG` - Contains a single locale, low priority.
-///
-/// # Example
-///
-/// ```ignore
-/// let locales: Vec<String> = _get(&env).collect();
-/// for locale in locales {
-///     println!("User's preferred locales: {}", locale);
-/// }
-/// ```
-fn _get(env: &impl EnvAccess) -> impl Iterator<Item = String> {
-    let mut locales = Vec::new();
-
-    // LANGUAGE contains one or multiple locales separated by colon (':')
-    if let Some(val) = env.get(LANGUAGE).filter(|val| !val.is_empty()) {
-        for part in val.split(':') {
-            let locale = posix_to_bcp47(part);
-            if !locales.contains(&locale) {
-                locales.push(locale);
-            }
-        }
-    }
+/// Like [get], but ignores the variables of [UNIX_LOCALE_VARS] named in `excluded`.
+pub(crate) fn get_without(excluded: &[&str]) -> LocaleList {
+    let locales = _get(&StdEnv, excluded);
 
-    // LC_ALL, LC_MESSAGES and LANG contain one locale
-    for variable in [LC_ALL, LC_MESSAGES, LANG] {
-        if let Some(val) = env.get(variable).filter(|val| !val.is_empty()) {
-            let locale = posix_to_bcp47(&val);
-            if !locales.contains(&locale) {
-                locales.push(locale);
-            }
-        }
+    // The environment always takes precedence, so the configuration files are only read when
+    // it doesn't name any locale. The user's own settings come before the system's defaults.
+    #[cfg(all(feature = "locale-conf", not(feature = "strict")))]
+    let locales = or_fallback(locales, || {
+        crate::locale_conf::read_user().map(|variables| _get(&variables, excluded))
+    });
+
+    #[cfg(all(feature = "kde", not(feature = "strict")))]
+    let locales = or_fallback(locales, || {
+        crate::kde::read().map(|settings| settings.locales(excluded).into_iter())
+    });
+
+    #[cfg(all(feature = "locale-conf", not(feature = "strict")))]
+    let locales = or_fallback(locales, || {
+        crate::locale_conf::read_system().map(|variables| _get(&variables, excluded))
+    });
+
+    LocaleList::new(locales)
+}
+
+/// Returns `locales`, or the ones from `fallback` if there are none.
+#[cfg(all(any(feature = "kde", feature = "locale-conf"), not(feature = "strict")))]
+fn or_fallback(locales: Locales, fallback: impl FnOnce() -> Option<Locales>) -> Locales {
+    if locales.as_slice().is_empty() {
+        fallback().unwrap_or(locales)
+    } else {
+        locales
     }
+}
 
-    locales.into_iter()
+/// Resolves the locales from `vars` like [get] does from the environment, without fallbacks.
+pub(crate) fn from_env(vars: &HashMap<String, String>) -> LocaleList {
+    LocaleList::new(_get(vars, &[]))
 }
 
-/// Converts a POSIX locale string to a BCP 47 locale string.
-///
-/// This function processes the input `code` by removing any character encoding
-/// (the part after the `.` character) and any modifiers (the part after the `@` character).
-/// It replaces underscores (`_`) with hyphens (`-`) to conform to BCP 47 formatting.
-///
-/// If the locale is already in the BCP 47 format, no changes are made.
-///
-/// Useful links:
-/// - [The Open Group Base Specifications Issue 8 - 7. Locale](https://pubs.opengroup.org/onlinepubs/9799919799/basedefs/V1_chap07.html)
-/// - [The Open Group Base Specifications Issue 8 - 8. Environment Variables](https://pubs.opengroup.org/onlinepubs/9799919799/basedefs/V1_chap08.html)
-/// - [BCP 47 specification](https://www.ietf.org/rfc/bcp/bcp47.html)
-///
-/// # Examples
-///
-/// ```ignore
-/// let bcp47 = posix_to_bcp47("en-US"); // already BCP 47
-/// assert_eq!(bcp47, "en-US"); // no changes
-///
-/// let bcp47 = posix_to_bcp47("en_US");
-/// assert_eq!(bcp47, "en-US");
-///
-/// let bcp47 = posix_to_bcp47("ru_RU.UTF-8");
-/// assert_eq!(bcp47, "ru-RU");
-///
-/// let bcp47 = posix_to_bcp47("fr_FR@dict");
-/// assert_eq!(bcp47, "fr-FR");
-///
-/// let bcp47 = posix_to_bcp47("de_DE.UTF-8@euro");
-/// assert_eq!(bcp47, "de-DE");
-/// ```
-///
-/// # TODO
-///
-/// 1. Implement POSIX to BCP 47 modifier conversion (see https://github.com/1Password/sys-locale/issues/32).
-/// 2. Optimize to avoid creating a new buffer (see https://github.com/1Password/sys-locale/pull/33).
-fn posix_to_bcp47(locale: &str) -> String {
-    locale
-        .chars()
-        .take_while(|&c| c != '.' && c != '@')
-        .map(|c| if c == '_' { '-' } else { c })
-        .collect()
+pub(crate) fn get_format() -> Option<String> {
+    let format = _get_format(&StdEnv);
+
+    #[cfg(all(feature = "locale-conf", not(feature = "strict")))]
+    let format = format.or_else(|| crate::locale_conf::read_user().and_then(|v| _get_format(&v)));
+
+    #[cfg(all(feature = "kde", not(feature = "strict")))]
+    let format = format.or_else(|| crate::kde::read().and_then(|settings| settings.format()));
+
+    #[cfg(all(feature = "locale-conf", not(feature = "strict")))]
+    let format = format.or_else(|| crate::locale_conf::read_system().and_then(|v| _get_format(&v)));
+
+    format
 }
 
-#[cfg(test)]
-mod tests {
-    use super::{EnvAccess, _get, posix_to_bcp47, LANG, LANGUAGE, LC_ALL, LC_MESSAGES};
-    use std::{
-        collections::HashMap,
-        ffi::{OsStr, OsString},
-    };
-
-    type MockEnv = HashMap<OsString, String>;
-    impl EnvAccess for MockEnv {
-        fn get(&self, key: impl AsRef<OsStr>) -> Option<String> {
-            self.get(key.as_ref()).cloned()
-        }
-    }
+pub(crate) fn get_regional() -> Option<String> {
+    let regional = _get_regional(&StdEnv);
+
+    #[cfg(all(feature = "locale-conf", not(feature = "strict")))]
+    let regional =
+        regional.or_else(|| crate::locale_conf::read_user().and_then(|v| _get_regional(&v)));
+
+    #[cfg(all(feature = "kde", not(feature = "strict")))]
+    let regional = regional.or_else(|| crate::kde::read().and_then(|settings| settings.format()));
 
-    const BCP_47: &str = "fr-FR";
-    const POSIX: &str = "fr_FR";
-    const POSIX_ENC: &str = "fr_FR.UTF-8";
-    const POSIX_MOD: &str = "fr_FR@euro";
-    const POSIX_ENC_MOD: &str = "fr_FR.UTF-8@euro";
-
-    #[test]
-    fn parse_identifier() {
-        assert_eq!(posix_to_bcp47(BCP_47), BCP_47);
-        assert_eq!(posix_to_bcp47(POSIX), BCP_47);
-        assert_eq!(posix_to_bcp47(POSIX_ENC), BCP_47);
-        assert_eq!(posix_to_bcp47(POSIX_MOD), BCP_47);
-        assert_eq!(posix_to_bcp47(POSIX_ENC_MOD), BCP_47);
+    #[cfg(all(feature = "locale-conf", not(feature = "strict")))]
+    let regional =
+        regional.or_else(|| crate::locale_conf::read_system().and_then(|v| _get_regional(&v)));
+
+    regional
+}
+
+/// Returns `$XDG_CONFIG_HOME`, or `$HOME/.config` when it's unset or not an absolute path.
+#[cfg(all(any(feature = "kde", feature = "locale-conf"), not(feature = "strict")))]
+pub(crate) fn config_dir() -> Option<std::path::PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(std::path::PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            env::var_os("HOME")
+                .filter(|home| !home.is_empty())
+                .map(|home| std::path::PathBuf::from(home).join(".config"))
+        })
+}
+
+pub(crate) fn explain(report: &mut String) -> fmt::Result {
+    _explain(&StdEnv, report)?;
+
+    #[cfg(all(feature = "kde", not(feature = "strict")))]
+    match crate::kde::read() {
+        Some(settings) => writeln!(
+            report,
+            "  plasma-localerc: LANGUAGE = {:?}, LANG = {:?} -> {:?}",
+            settings.language,
+            settings.formats,
+            settings.locales(&[])
+        )?,
+        None => writeln!(report, "  plasma-localerc: not found")?,
     }
 
-    #[test]
-    fn env_get() {
-        fn case(
-            env: &mut MockEnv,
-            language: impl Into<String>,
-            lc_all: impl Into<String>,
-            lc_messages: impl Into<String>,
-            lang: impl Into<String>,
-            expected: impl IntoIterator<Item = impl Into<String>>,
-        ) {
-            env.insert(LANGUAGE.into(), language.into());
-            env.insert(LC_ALL.into(), lc_all.into());
-            env.insert(LC_MESSAGES.into(), lc_messages.into());
-            env.insert(LANG.into(), lang.into());
-            assert!(_get(env).eq(expected.into_iter().map(|s| s.into())));
+    #[cfg(all(feature = "locale-conf", not(feature = "strict")))]
+    for (name, variables) in [
+        ("user locale.conf", crate::locale_conf::read_user()),
+        ("/etc/locale.conf", crate::locale_conf::read_system()),
+    ] {
+        match variables {
+            Some(variables) => writeln!(
+                report,
+                "  {}: {:?} -> {:?}",
+                name,
+                variables,
+                _get(&variables, &[]).collect::<Vec<_>>()
+            )?,
+            None => writeln!(report, "  {}: not found", name)?,
         }
-
-        let mut env = MockEnv::new();
-        assert_eq!(_get(&env).next(), None);
-
-        // Empty
-        case(&mut env, "", "", "", "", &[] as &[String]);
-
-        // Constants
-        case(
-            &mut env,
-            POSIX_ENC_MOD,
-            POSIX_ENC,
-            POSIX_MOD,
-            POSIX,
-            [BCP_47],
-        );
-
-        // Only one variable
-        case(&mut env, "en_US", "", "", "", ["en-US"]);
-        case(&mut env, "", "en_US", "", "", ["en-US"]);
-        case(&mut env, "", "", "en_US", "", ["en-US"]);
-        case(&mut env, "", "", "", "en_US", ["en-US"]);
-
-        // Duplicates
-        case(&mut env, "en_US", "en_US", "en_US", "en_US", ["en-US"]);
-        case(
-            &mut env,
-            "en_US",
-            "en_US",
-            "ru_RU",
-            "en_US",
-            ["en-US", "ru-RU"],
-        );
-        case(
-            &mut env,
-            "en_US",
-            "ru_RU",
-            "ru_RU",
-            "en_US",
-            ["en-US", "ru-RU"],
-        );
-        case(
-            &mut env,
-            "en_US",
-            "es_ES",
-            "ru_RU",
-            "en_US",
-            ["en-US", "es-ES", "ru-RU"],
-        );
-        case(
-            &mut env,
-            "en_US:ru_RU:es_ES:en_US",
-            "es_ES",
-            "ru_RU",
-            "en_US",
-            ["en-US", "ru-RU", "es-ES"],
-        );
-
-        // Duplicates with different case
-        case(
-            &mut env,
-            "en_US:fr_fr",
-            "EN_US",
-            "fR_Fr",
-            "En_US",
-            ["en-US", "fr-fr", "EN-US", "fR-Fr", "En-US"],
-        );
-
-        // More complicated cases
-        case(
-            &mut env,
-            "ru_RU:ru:en_US:en",
-            "ru_RU.UTF-8",
-            "ru_RU.UTF-8",
-            "ru_RU.UTF-8",
-            ["ru-RU", "ru", "en-US", "en"],
-        );
-        case(
-            &mut env,
-            "fr_FR.UTF-8@euro:fr_FR.UTF-8:fr_FR:fr:en_US.UTF-8:en_US:en",
-            "es_ES.UTF-8@euro",
-            "fr_FR.UTF-8@euro",
-            "fr_FR.UTF-8@euro",
-            ["fr-FR", "fr", "en-US", "en", "es-ES"],
-        );
-        case(
-            &mut env,
-            "",
-            "es_ES.UTF-8@euro",
-            "fr_FR.UTF-8@euro",
-            "fr_FR.UTF-8@euro",
-            ["es-ES", "fr-FR"],
-        );
-        case(
-            &mut env,
-            "fr_FR@euro",
-            "fr_FR.UTF-8",
-            "en_US.UTF-8",
-            "en_US.UTF-8@dict",
-            ["fr-FR", "en-US"],
-        );
-
-        // Already BCP 47
-        case(&mut env, BCP_47, BCP_47, BCP_47, POSIX, [BCP_47]);
-        case(
-            &mut env,
-            "fr-FR",
-            "es-ES",
-            "de-DE",
-            "en-US",
-            ["fr-FR", "es-ES", "de-DE", "en-US"],
-        );
     }
+
+    Ok(())
+}
+
+pub(crate) fn get_categories() -> BTreeMap<Category, String> {
+    _get_categories(&StdEnv)
+}
+
+pub(crate) fn get_timezone() -> Option<String> {
+    _get_timezone(&StdEnv)
+        .or_else(|| {
+            let contents = fs::read_to_string("/etc/timezone").ok()?;
+            zone_id(contents.trim()).map(String::from)
+        })
+        .or_else(|| {
+            let target = fs::read_link("/etc/localtime").ok()?;
+            target.to_str().and_then(zone_id).map(String::from)
+        })
+        .or_else(default_init_timezone)
+}
+
+/// Reads the system's time zone from `/etc/default/init`, where illumos and Solaris set it for
+/// every process, since they have no `/etc/localtime`.
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+fn default_init_timezone() -> Option<String> {
+    let contents = fs::read_to_string("/etc/default/init").ok()?;
+    crate::unix_env::init_timezone(&contents).map(String::from)
+}
+
+#[cfg(not(any(target_os = "illumos", target_os = "solaris")))]
+fn default_init_timezone() -> Option<String> {
+    None
 }
@@ -0,0 +1,782 @@
+extern crate std;
+
+use alloc::{string::String, vec, vec::Vec};
+use core::{fmt, fmt::Write};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsStr,
+};
+
+use crate::{posix::posix_to_bcp47, Category};
+
+pub(crate) const LANGUAGE: &str = "LANGUAGE";
+const LC_ALL: &str = "LC_ALL";
+const LC_MESSAGES: &str = "LC_MESSAGES";
+const LC_TIME: &str = "LC_TIME";
+const LC_NUMERIC: &str = "LC_NUMERIC";
+const LC_MONETARY: &str = "LC_MONETARY";
+const LC_PAPER: &str = "LC_PAPER";
+const LC_MEASUREMENT: &str = "LC_MEASUREMENT";
+pub(crate) const LANG: &str = "LANG";
+const TZ: &str = "TZ";
+
+/// The variables [_get] consults for the preferred locales, in descending order of priority.
+const UNIX_LOCALE_VARS: [&str; 4] = [LANGUAGE, LC_ALL, LC_MESSAGES, LANG];
+
+/// Environment variable access abstraction to allow testing without
+/// mutating env variables.
+///
+/// The Unix provider implements it to query [std::env].
+pub(crate) trait EnvAccess {
+    /// See also [std::env::var]
+    fn get(&self, key: impl AsRef<OsStr>) -> Option<String>;
+}
+
+/// A snapshot of environment variables
+impl EnvAccess for HashMap<String, String> {
+    fn get(&self, key: impl AsRef<OsStr>) -> Option<String> {
+        key.as_ref().to_str().and_then(|key| self.get(key)).cloned()
+    }
+}
+
+/// Reads the `TZ` assignment from the contents of `/etc/default/init`, where the last one wins
+/// like in the shell script it is.
+#[cfg(any(target_os = "illumos", target_os = "solaris", test))]
+pub(crate) fn init_timezone(contents: &str) -> Option<&str> {
+    contents
+        .lines()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix("TZ="))
+        .map(unquote)
+        .and_then(zone_id)
+}
+
+/// Retrieves a list of unique locales by checking specific environment variables
+/// in a predefined order: LANGUAGE, LC_ALL, LC_MESSAGES, and LANG.
+///
+/// The function first checks the `LANGUAGE` environment variable, which can contain
+/// one or more locales separated by a colon (`:`). It then splits these values,
+/// converts them from [POSIX](https://pubs.opengroup.org/onlinepubs/9799919799/basedefs/V1_chap08.html)
+/// to [BCP 47](https://www.ietf.org/rfc/bcp/bcp47.html) format, and adds them to the list of locales
+/// if they are not already included.
+///
+/// Next, the function checks the `LC_ALL`, `LC_MESSAGES`, and `LANG` environment
+/// variables. Each of these variables contains a single locale. If a locale is found,
+/// and it's not empty, it is converted to BCP 47 format and added to the list if
+/// it is not already included.
+///
+/// The `C` and `POSIX` locales (including `C.UTF-8`) are skipped wherever they appear, since
+/// they don't name a language, and resolution continues with the next variable.
+///
+/// For more information check this issue: https://github.com/1Password/sys-locale/issues/14.
+///
+/// The function ensures that locales are returned in the order of precedence
+/// and without duplicates. The final list of locales is returned as an iterator.
+///
+/// # Returns
+///
+/// An iterator over the unique locales found in the environment variables.
+///
+/// # Environment Variables Checked
+///
+/// 1. `LANGUAGE` - Can contain multiple locales, each separated by a colon (`:`), highest priority.
+/// 2. `LC_ALL` - Contains a single locale, high priority.
+/// 3. `LC_MESSAGES` - Contains a single locale, medium priority.
+/// 4. `LANG` - Contains a single locale, low priority.
+///
+/// Variables named in `excluded` are skipped, as if they were unset.
+///
+/// # Example
+///
+/// ```ignore
+/// let locales: Vec<String> = _get(&env, &[]).collect();
+/// for locale in locales {
+///     println!("User's preferred locales: {}", locale);
+/// }
+/// ```
+pub(crate) fn _get(env: &impl EnvAccess, excluded: &[&str]) -> vec::IntoIter<String> {
+    let mut locales = Vec::new();
+
+    let variables = UNIX_LOCALE_VARS
+        .iter()
+        .filter(|variable| !excluded.contains(variable));
+    for &variable in variables {
+        let names = read_names(env, variable).unwrap_or_default();
+        for locale in names.iter().filter_map(|name| posix_to_bcp47(name)) {
+            if !locales.contains(&locale) {
+                locales.push(locale);
+            }
+        }
+    }
+
+    locales.into_iter()
+}
+
+/// Retrieves the locale used for formatting dates, times, numbers, and currency.
+///
+/// Unlike [_get], this never consults `LANGUAGE`, which only applies to message
+/// translations. The first non-empty value of `LC_ALL`, `LC_TIME`, `LC_NUMERIC`,
+/// `LC_MONETARY`, or `LANG` is converted to BCP 47 and returned.
+pub(crate) fn _get_format(env: &impl EnvAccess) -> Option<String> {
+    [LC_ALL, LC_TIME, LC_NUMERIC, LC_MONETARY, LANG]
+        .iter()
+        .filter_map(|variable| read_single(env, variable))
+        .find_map(|val| posix_to_bcp47(&val))
+}
+
+/// Retrieves the locale describing the user's region, as opposed to their language.
+///
+/// A user may read English but live in Germany, with `LANG=en_US.UTF-8` and
+/// `LC_PAPER=de_DE.UTF-8`. The categories tied to the place the user lives in are consulted
+/// first, so the first value of `LC_ALL`, `LC_PAPER`, `LC_MEASUREMENT`, `LC_TIME`, or `LANG`
+/// that names a language is converted to BCP 47 and returned.
+pub(crate) fn _get_regional(env: &impl EnvAccess) -> Option<String> {
+    [LC_ALL, LC_PAPER, LC_MEASUREMENT, LC_TIME, LANG]
+        .iter()
+        .filter_map(|variable| read_single(env, variable))
+        .find_map(|val| posix_to_bcp47(&val))
+}
+
+/// Lists every variable read by [_get] and [_get_format], along with how each value was
+/// converted.
+pub(crate) fn _explain(env: &impl EnvAccess, report: &mut String) -> fmt::Result {
+    writeln!(report, "Provider: environment variables")?;
+    for variable in [
+        LANGUAGE,
+        LC_ALL,
+        LC_MESSAGES,
+        LANG,
+        LC_TIME,
+        LC_NUMERIC,
+        LC_MONETARY,
+    ] {
+        let value = match env.get(variable) {
+            Some(value) => value,
+            None => {
+                writeln!(report, "  {} is unset", variable)?;
+                continue;
+            }
+        };
+        let names = match read_names(env, variable) {
+            Some(names) => names,
+            None if read(env, variable).is_some() => {
+                writeln!(report, "  {} = {:?}", variable, value)?;
+                writeln!(report, "    skipped, not a single locale")?;
+                continue;
+            }
+            None => {
+                writeln!(report, "  {} is empty", variable)?;
+                continue;
+            }
+        };
+
+        writeln!(report, "  {} = {:?}", variable, value)?;
+        for name in names {
+            match posix_to_bcp47(&name) {
+                Some(locale) => writeln!(report, "    {:?} -> {}", name, locale)?,
+                None => writeln!(report, "    {:?} -> skipped, no language", name)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Retrieves the locale of every category, following the precedence of `setlocale`: `LC_ALL`,
+/// then the category's own variable, then `LANG`.
+///
+/// Empty variables are treated as unset. Categories resolving to the `C` or `POSIX` locale
+/// are left out.
+pub(crate) fn _get_categories(env: &impl EnvAccess) -> BTreeMap<Category, String> {
+    let get = |variable: &str| read_single(env, variable);
+    let all = get(LC_ALL);
+    let lang = get(LANG);
+
+    Category::ALL
+        .iter()
+        .filter_map(|category| {
+            let value = all
+                .clone()
+                .or_else(|| get(category.variable()))
+                .or_else(|| lang.clone())?;
+            posix_to_bcp47(&value).map(|locale| (*category, locale))
+        })
+        .collect()
+}
+
+/// Reads `variable`, treating an empty value as unset.
+///
+/// Whitespace and quotes are removed first, so a value that is only blanks or a pair of quotes
+/// is empty too, and resolution continues with the next variable.
+fn read(env: &impl EnvAccess, variable: &str) -> Option<String> {
+    env.get(variable)
+        .map(|value| String::from(unquote(value.trim()).trim()))
+        .filter(|value| !value.is_empty())
+}
+
+/// Reads `variable` like [read], for variables that hold a single locale.
+///
+/// Only `LANGUAGE` is a `:` separated list. A value like `LC_ALL=en_US:fr_FR` is a
+/// misconfiguration that `setlocale` rejects as a whole, so it's treated as unset instead of
+/// guessing which of its locales was meant.
+fn read_single(env: &impl EnvAccess, variable: &str) -> Option<String> {
+    read(env, variable).filter(|value| !value.contains(':'))
+}
+
+/// Reads `variable` like [_get] does and splits it into the locale names it holds: every
+/// non-empty segment of `LANGUAGE`, which contains one or more locales separated by `:`, or the
+/// single value of any other variable.
+///
+/// Returns [`None`] if the variable is unset or empty, or holds a list where a single locale
+/// is expected.
+fn read_names(env: &impl EnvAccess, variable: &str) -> Option<Vec<String>> {
+    if variable == LANGUAGE {
+        // Empty segments, like the one in `en_US::fr_FR`, don't name a locale.
+        let value = read(env, variable)?;
+        Some(
+            value
+                .split(':')
+                .filter(|segment| !segment.is_empty())
+                .map(String::from)
+                .collect(),
+        )
+    } else {
+        read_single(env, variable).map(|value| vec![value])
+    }
+}
+
+/// Removes a single pair of matching `"` or `'` quotes around `value`.
+///
+/// Some dotfiles and container `ENV` declarations end up exporting `LANG="en_US.UTF-8"` with
+/// the quotes included, which would otherwise make the whole value unusable.
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+/// Retrieves the time zone named by `TZ`, if it's set to an IANA time zone id.
+///
+/// `TZ` can also hold a POSIX rule like `CET-1CEST,M3.5.0,M10.5.0/3`, which doesn't name a
+/// zone, in which case the system's configured zone is used instead.
+pub(crate) fn _get_timezone(env: &impl EnvAccess) -> Option<String> {
+    env.get(TZ).and_then(|tz| zone_id(&tz).map(String::from))
+}
+
+/// Extracts an IANA time zone id from a `TZ` value, the contents of `/etc/timezone`, or the
+/// target of the `/etc/localtime` link.
+///
+/// Paths into the time zone database, like `/usr/share/zoneinfo/Europe/Paris`, are reduced
+/// to the id at their end.
+pub(crate) fn zone_id(value: &str) -> Option<&str> {
+    // A leading `:` marks an implementation defined format, which is a path or id in practice.
+    let value = value.strip_prefix(':').unwrap_or(value);
+    let id = match value.rfind("zoneinfo/") {
+        Some(start) => &value[start + "zoneinfo/".len()..],
+        None => value,
+    };
+    // These are copies of the database that only differ in how they handle leap seconds.
+    let id = id
+        .strip_prefix("posix/")
+        .or_else(|| id.strip_prefix("right/"))
+        .unwrap_or(id);
+
+    let valid = id
+        .split('/')
+        .all(|part| !part.is_empty() && part != "." && part != "..")
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"/_+-".contains(&b));
+    Some(id).filter(|_| valid)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::{
+        _explain, _get, _get_categories, _get_format, _get_regional, _get_timezone, init_timezone,
+        posix_to_bcp47, unquote, zone_id, Category, EnvAccess, LANG, LANGUAGE, LC_ALL,
+        LC_MEASUREMENT, LC_MESSAGES, LC_NUMERIC, LC_PAPER, LC_TIME, TZ, UNIX_LOCALE_VARS,
+    };
+    use alloc::{string::String, vec::Vec};
+    use std::{
+        collections::HashMap,
+        ffi::{OsStr, OsString},
+    };
+
+    type MockEnv = HashMap<OsString, String>;
+    impl EnvAccess for MockEnv {
+        fn get(&self, key: impl AsRef<OsStr>) -> Option<String> {
+            self.get(key.as_ref()).cloned()
+        }
+    }
+
+    const BCP_47: &str = "fr-FR";
+    const POSIX: &str = "fr_FR";
+    const POSIX_ENC: &str = "fr_FR.UTF-8";
+    const POSIX_MOD: &str = "fr_FR@euro";
+    const POSIX_ENC_MOD: &str = "fr_FR.UTF-8@euro";
+
+    #[test]
+    fn parse_identifier() {
+        assert_eq!(posix_to_bcp47(BCP_47).as_deref(), Some(BCP_47));
+        assert_eq!(posix_to_bcp47(POSIX).as_deref(), Some(BCP_47));
+        assert_eq!(posix_to_bcp47(POSIX_ENC).as_deref(), Some(BCP_47));
+        assert_eq!(posix_to_bcp47(POSIX_MOD).as_deref(), Some(BCP_47));
+        assert_eq!(posix_to_bcp47(POSIX_ENC_MOD).as_deref(), Some(BCP_47));
+    }
+
+    #[test]
+    fn parse_c_locale() {
+        for locale in ["C", "C.UTF-8", "C.utf8", "POSIX", "POSIX.UTF-8", "C@euro"] {
+            assert_eq!(posix_to_bcp47(locale), None, "{} was converted", locale);
+        }
+
+        // Only the full language name is special
+        assert_eq!(posix_to_bcp47("ca_ES").as_deref(), Some("ca-ES"));
+    }
+
+    #[test]
+    fn parse_missing_language() {
+        for locale in ["_US", "_US.UTF-8", "-US", "_", "_US@euro"] {
+            assert_eq!(posix_to_bcp47(locale), None, "{} was converted", locale);
+        }
+    }
+
+    #[test]
+    fn parse_dangling_separators() {
+        for locale in [
+            "en_US.",
+            "en_US@",
+            "en_US.@",
+            "en_US @euro",
+            " en_US .UTF-8",
+            "en_US\t",
+            "en_US_",
+            "en_US-.UTF-8",
+        ] {
+            assert_eq!(
+                posix_to_bcp47(locale).as_deref(),
+                Some("en-US"),
+                "{:?} wasn't cleaned up",
+                locale
+            );
+        }
+        assert_eq!(posix_to_bcp47("C .UTF-8"), None);
+        assert_eq!(posix_to_bcp47(" _US"), None);
+    }
+
+    #[test]
+    fn env_get() {
+        fn case(
+            env: &mut MockEnv,
+            language: impl Into<String>,
+            lc_all: impl Into<String>,
+            lc_messages: impl Into<String>,
+            lang: impl Into<String>,
+            expected: impl IntoIterator<Item = impl Into<String>>,
+        ) {
+            env.insert(LANGUAGE.into(), language.into());
+            env.insert(LC_ALL.into(), lc_all.into());
+            env.insert(LC_MESSAGES.into(), lc_messages.into());
+            env.insert(LANG.into(), lang.into());
+            assert!(_get(env, &[]).eq(expected.into_iter().map(|s| s.into())));
+        }
+
+        let mut env = MockEnv::new();
+        assert_eq!(_get(&env, &[]).next(), None);
+
+        // Empty
+        case(&mut env, "", "", "", "", &[] as &[String]);
+
+        // Constants
+        case(
+            &mut env,
+            POSIX_ENC_MOD,
+            POSIX_ENC,
+            POSIX_MOD,
+            POSIX,
+            [BCP_47],
+        );
+
+        // Only one variable
+        case(&mut env, "en_US", "", "", "", ["en-US"]);
+        case(&mut env, "", "en_US", "", "", ["en-US"]);
+        case(&mut env, "", "", "en_US", "", ["en-US"]);
+        case(&mut env, "", "", "", "en_US", ["en-US"]);
+
+        // Duplicates
+        case(&mut env, "en_US", "en_US", "en_US", "en_US", ["en-US"]);
+        case(
+            &mut env,
+            "en_US",
+            "en_US",
+            "ru_RU",
+            "en_US",
+            ["en-US", "ru-RU"],
+        );
+        case(
+            &mut env,
+            "en_US",
+            "ru_RU",
+            "ru_RU",
+            "en_US",
+            ["en-US", "ru-RU"],
+        );
+        case(
+            &mut env,
+            "en_US",
+            "es_ES",
+            "ru_RU",
+            "en_US",
+            ["en-US", "es-ES", "ru-RU"],
+        );
+        case(
+            &mut env,
+            "en_US:ru_RU:es_ES:en_US",
+            "es_ES",
+            "ru_RU",
+            "en_US",
+            ["en-US", "ru-RU", "es-ES"],
+        );
+
+        // Duplicates with different case
+        case(
+            &mut env,
+            "en_US:fr_fr",
+            "EN_US",
+            "fR_Fr",
+            "En_US",
+            ["en-US", "fr-fr", "EN-US", "fR-Fr", "En-US"],
+        );
+
+        // More complicated cases
+        case(
+            &mut env,
+            "ru_RU:ru:en_US:en",
+            "ru_RU.UTF-8",
+            "ru_RU.UTF-8",
+            "ru_RU.UTF-8",
+            ["ru-RU", "ru", "en-US", "en"],
+        );
+        case(
+            &mut env,
+            "fr_FR.UTF-8@euro:fr_FR.UTF-8:fr_FR:fr:en_US.UTF-8:en_US:en",
+            "es_ES.UTF-8@euro",
+            "fr_FR.UTF-8@euro",
+            "fr_FR.UTF-8@euro",
+            ["fr-FR", "fr", "en-US", "en", "es-ES"],
+        );
+        case(
+            &mut env,
+            "",
+            "es_ES.UTF-8@euro",
+            "fr_FR.UTF-8@euro",
+            "fr_FR.UTF-8@euro",
+            ["es-ES", "fr-FR"],
+        );
+        case(
+            &mut env,
+            "fr_FR@euro",
+            "fr_FR.UTF-8",
+            "en_US.UTF-8",
+            "en_US.UTF-8@dict",
+            ["fr-FR", "en-US"],
+        );
+
+        // C locales fall through to the next variable
+        case(&mut env, "", "C.UTF-8", "", "en_US", ["en-US"]);
+        case(&mut env, "C.utf8", "", "POSIX.UTF-8", "fr_FR", [BCP_47]);
+        case(&mut env, "C:fr_FR", "C", "C", "C.UTF-8", [BCP_47]);
+        case(&mut env, "", "C.UTF-8", "C.utf8", "POSIX", &[] as &[String]);
+
+        // Empty LANGUAGE segments are skipped
+        case(&mut env, ":en_US:", "", "", "", ["en-US"]);
+        case(&mut env, "en_US::fr_FR", "", "", "", ["en-US", "fr-FR"]);
+        case(&mut env, "::", "", "", "de_DE", ["de-DE"]);
+
+        // Locales without a language fall through to the next variable
+        case(&mut env, "", "_US", "", "en_US", ["en-US"]);
+        case(&mut env, "_US.UTF-8:fr_FR", "", "-US", "", ["fr-FR"]);
+        case(&mut env, "", "", "", "_US.UTF-8", &[] as &[String]);
+
+        // Already BCP 47
+        case(&mut env, BCP_47, BCP_47, BCP_47, POSIX, [BCP_47]);
+        case(
+            &mut env,
+            "fr-FR",
+            "es-ES",
+            "de-DE",
+            "en-US",
+            ["fr-FR", "es-ES", "de-DE", "en-US"],
+        );
+    }
+
+    #[test]
+    fn env_get_blank() {
+        for blank in ["   ", "\t\n", "\" \""] {
+            let mut env = MockEnv::new();
+            env.insert(LC_ALL.into(), blank.into());
+            env.insert(LC_TIME.into(), "de_DE.UTF-8".into());
+            env.insert(LANG.into(), "en_US.UTF-8".into());
+
+            assert_eq!(_get(&env, &[]).collect::<Vec<String>>(), ["en-US"]);
+            assert_eq!(_get_format(&env).as_deref(), Some("de-DE"));
+            assert_eq!(
+                _get_categories(&env)
+                    .get(&Category::Time)
+                    .map(String::as_str),
+                Some("de-DE")
+            );
+        }
+    }
+
+    #[test]
+    fn env_get_quoted() {
+        assert_eq!(unquote("\"en_US\""), "en_US");
+        assert_eq!(unquote("'en_US'"), "en_US");
+        assert_eq!(unquote("en_US"), "en_US");
+        assert_eq!(unquote("\"\"en_US\"\""), "\"en_US\"");
+        assert_eq!(unquote("\"en_US'"), "\"en_US'");
+        assert_eq!(unquote("\"en_US"), "\"en_US");
+        assert_eq!(unquote("\""), "\"");
+        assert_eq!(unquote("''"), "");
+
+        let mut env = MockEnv::new();
+        env.insert(LANG.into(), "\"en_US\"".into());
+        assert_eq!(_get(&env, &[]).collect::<Vec<String>>(), ["en-US"]);
+        assert_eq!(_get_format(&env).as_deref(), Some("en-US"));
+        assert_eq!(
+            _get_categories(&env)
+                .get(&Category::Time)
+                .map(String::as_str),
+            Some("en-US")
+        );
+
+        env.insert(LANGUAGE.into(), "'fr_FR:de_DE'".into());
+        env.insert(LC_ALL.into(), "\"\"".into());
+        assert_eq!(
+            _get(&env, &[]).collect::<Vec<String>>(),
+            ["fr-FR", "de-DE", "en-US"]
+        );
+        assert_eq!(_get_format(&env).as_deref(), Some("en-US"));
+    }
+
+    #[test]
+    fn env_get_list_in_single() {
+        let mut env = MockEnv::new();
+        env.insert(LC_ALL.into(), "en_US:fr_FR".into());
+        env.insert(LC_TIME.into(), "de_DE:".into());
+        env.insert(LANG.into(), "es_ES.UTF-8".into());
+
+        assert_eq!(_get(&env, &[]).collect::<Vec<String>>(), ["es-ES"]);
+        assert_eq!(_get_format(&env).as_deref(), Some("es-ES"));
+        assert_eq!(
+            _get_categories(&env)
+                .get(&Category::Time)
+                .map(String::as_str),
+            Some("es-ES")
+        );
+
+        // LANGUAGE is still split
+        env.insert(LANGUAGE.into(), "en_US:fr_FR".into());
+        assert_eq!(
+            _get(&env, &[]).collect::<Vec<String>>(),
+            ["en-US", "fr-FR", "es-ES"]
+        );
+    }
+
+    #[test]
+    fn env_get_without() {
+        let mut env = MockEnv::new();
+        env.insert(LANGUAGE.into(), "fr_FR:de_DE".into());
+        env.insert(LC_ALL.into(), "es_ES.UTF-8".into());
+        env.insert(LANG.into(), "en_US.UTF-8".into());
+
+        let get = |excluded: &[&str]| _get(&env, excluded).collect::<Vec<String>>();
+        assert_eq!(get(&[LANGUAGE]), ["es-ES", "en-US"]);
+        assert_eq!(get(&[LANGUAGE, LC_ALL]), ["en-US"]);
+        assert_eq!(get(&[LC_ALL, LC_MESSAGES]), ["fr-FR", "de-DE", "en-US"]);
+        assert!(get(&UNIX_LOCALE_VARS).is_empty());
+
+        // Names are matched exactly, and unknown ones are ignored
+        assert_eq!(get(&["language", "TZ"]), get(&[]));
+    }
+
+    #[test]
+    fn env_get_format() {
+        let mut env = MockEnv::new();
+        assert_eq!(_get_format(&env), None);
+
+        // `LANGUAGE` only affects messages
+        env.insert(LANGUAGE.into(), "ru_RU:en_US".into());
+        assert_eq!(_get_format(&env), None);
+
+        env.insert(LANG.into(), "en_US.UTF-8".into());
+        assert_eq!(_get_format(&env).as_deref(), Some("en-US"));
+
+        // `LC_MESSAGES` only affects messages
+        env.insert(LC_MESSAGES.into(), "es_ES.UTF-8".into());
+        assert_eq!(_get_format(&env).as_deref(), Some("en-US"));
+
+        env.insert(LC_NUMERIC.into(), "de_DE.UTF-8".into());
+        assert_eq!(_get_format(&env).as_deref(), Some("de-DE"));
+
+        env.insert(LC_TIME.into(), "fr_FR.UTF-8".into());
+        assert_eq!(_get_format(&env).as_deref(), Some("fr-FR"));
+
+        env.insert(LC_ALL.into(), "".into());
+        assert_eq!(_get_format(&env).as_deref(), Some("fr-FR"));
+
+        env.insert(LC_ALL.into(), "it_IT".into());
+        assert_eq!(_get_format(&env).as_deref(), Some("it-IT"));
+    }
+
+    #[test]
+    fn env_get_regional() {
+        let mut env = MockEnv::new();
+        assert_eq!(_get_regional(&env), None);
+
+        env.insert(LANGUAGE.into(), "en".into());
+        env.insert(LANG.into(), "en_US.UTF-8".into());
+        env.insert(LC_NUMERIC.into(), "fr_FR.UTF-8".into());
+        assert_eq!(_get_regional(&env).as_deref(), Some("en-US"));
+
+        env.insert(LC_TIME.into(), "en_GB.UTF-8".into());
+        assert_eq!(_get_regional(&env).as_deref(), Some("en-GB"));
+
+        env.insert(LC_MEASUREMENT.into(), "C".into());
+        assert_eq!(_get_regional(&env).as_deref(), Some("en-GB"));
+
+        env.insert(LC_PAPER.into(), "de_DE.UTF-8".into());
+        assert_eq!(_get_regional(&env).as_deref(), Some("de-DE"));
+
+        env.insert(LC_ALL.into(), "it_IT".into());
+        assert_eq!(_get_regional(&env).as_deref(), Some("it-IT"));
+    }
+
+    #[test]
+    fn parse_zone_id() {
+        assert_eq!(zone_id("Europe/Paris"), Some("Europe/Paris"));
+        assert_eq!(zone_id(":America/New_York"), Some("America/New_York"));
+        assert_eq!(zone_id("UTC"), Some("UTC"));
+        assert_eq!(zone_id("Etc/GMT+5"), Some("Etc/GMT+5"));
+        assert_eq!(
+            zone_id("/usr/share/zoneinfo/America/Argentina/Buenos_Aires"),
+            Some("America/Argentina/Buenos_Aires")
+        );
+        assert_eq!(
+            zone_id("../usr/share/zoneinfo/posix/Asia/Tokyo"),
+            Some("Asia/Tokyo")
+        );
+        assert_eq!(
+            zone_id("/var/db/timezone/zoneinfo/right/Europe/Oslo"),
+            Some("Europe/Oslo")
+        );
+
+        assert_eq!(zone_id(""), None);
+        assert_eq!(zone_id(":"), None);
+        assert_eq!(zone_id("/etc/localtime"), None);
+        assert_eq!(zone_id("CET-1CEST,M3.5.0,M10.5.0/3"), None);
+        assert_eq!(zone_id("<+03>-3"), None);
+        assert_eq!(zone_id("/usr/share/zoneinfo/"), None);
+        assert_eq!(zone_id("../../etc/passwd"), None);
+    }
+
+    #[test]
+    fn parse_init_timezone() {
+        assert_eq!(
+            init_timezone("# Default settings\nTZ=US/Pacific\nCMASK=022\nLANG=en_US.UTF-8\n"),
+            Some("US/Pacific")
+        );
+        assert_eq!(init_timezone("TZ=\"Europe/Paris\"\n"), Some("Europe/Paris"));
+        assert_eq!(init_timezone("#TZ=Europe/Paris\nCMASK=022\n"), None);
+        assert_eq!(init_timezone("TZ=PST8PDT,M3.2.0,M11.1.0\n"), None);
+        assert_eq!(init_timezone(""), None);
+    }
+
+    #[test]
+    fn env_get_timezone() {
+        let mut env = MockEnv::new();
+        assert_eq!(_get_timezone(&env), None);
+
+        env.insert(TZ.into(), "".into());
+        assert_eq!(_get_timezone(&env), None);
+
+        env.insert(TZ.into(), ":Europe/Berlin".into());
+        assert_eq!(_get_timezone(&env).as_deref(), Some("Europe/Berlin"));
+
+        env.insert(TZ.into(), "EST5EDT,M3.2.0,M11.1.0".into());
+        assert_eq!(_get_timezone(&env), None);
+    }
+
+    #[test]
+    fn env_get_categories() {
+        let mut env = MockEnv::new();
+        assert!(_get_categories(&env).is_empty());
+
+        // `LANG` applies to every category
+        env.insert(LANG.into(), "en_US.UTF-8".into());
+        let categories = _get_categories(&env);
+        assert_eq!(categories.len(), Category::ALL.len());
+        assert!(categories.values().all(|locale| locale == "en-US"));
+
+        // Each category's own variable overrides `LANG`, and `LANGUAGE` is ignored
+        env.insert(LANGUAGE.into(), "fr_FR".into());
+        env.insert(LC_TIME.into(), "de_DE.UTF-8".into());
+        env.insert("LC_PAPER".into(), "C".into());
+        env.insert("LC_COLLATE".into(), "".into());
+        let categories = _get_categories(&env);
+        assert_eq!(categories[&Category::Time], "de-DE");
+        assert_eq!(categories[&Category::Collate], "en-US");
+        assert_eq!(categories[&Category::Messages], "en-US");
+        assert_eq!(categories.get(&Category::Paper), None);
+
+        // `LC_ALL` overrides everything
+        env.insert(LC_ALL.into(), "ja_JP.UTF-8".into());
+        let categories = _get_categories(&env);
+        assert_eq!(categories.len(), Category::ALL.len());
+        assert!(categories.values().all(|locale| locale == "ja-JP"));
+    }
+
+    #[test]
+    fn env_explain() {
+        let mut env = MockEnv::new();
+        env.insert(LANGUAGE.into(), "fr_FR::C".into());
+        env.insert(LC_ALL.into(), "".into());
+        env.insert(LC_MESSAGES.into(), " 'de_DE.UTF-8' ".into());
+        env.insert(LANG.into(), "en_US.UTF-8".into());
+        env.insert(LC_TIME.into(), "en_US:fr_FR".into());
+
+        let mut report = String::new();
+        _explain(&env, &mut report).unwrap();
+        let expected = [
+            "Provider: environment variables",
+            "  LANGUAGE = \"fr_FR::C\"",
+            "    \"fr_FR\" -> fr-FR",
+            "    \"C\" -> skipped, no language",
+            "  LC_ALL is empty",
+            "  LC_MESSAGES = \" 'de_DE.UTF-8' \"",
+            "    \"de_DE.UTF-8\" -> de-DE",
+            "  LANG = \"en_US.UTF-8\"",
+            "    \"en_US.UTF-8\" -> en-US",
+            "  LC_TIME = \"en_US:fr_FR\"",
+            "    skipped, not a single locale",
+            "  LC_NUMERIC is unset",
+            "  LC_MONETARY is unset",
+        ];
+        assert!(report.lines().eq(expected.iter().copied()), "{}", report);
+
+        // The report lists the same locales as are resolved
+        assert_eq!(
+            _get(&env, &[]).collect::<Vec<String>>(),
+            ["fr-FR", "de-DE", "en-US"]
+        );
+    }
+}
@@ -1,8 +1,11 @@
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
+use core::{fmt, fmt::Write};
 
-use js_sys::{JsString, Object};
+use js_sys::{Array, Function, JsString, Object, Reflect};
 use wasm_bindgen::{prelude::*, JsCast, JsValue};
 
+use crate::{locale::normalize_reported, reflect::property, LocaleList};
+
 #[derive(Clone)]
 enum GlobalType {
     Window(web_sys::Window),
@@ -43,14 +46,100 @@ fn global() -> GlobalType {
     }
 }
 
-pub(crate) fn get() -> impl Iterator<Item = String> {
+pub(crate) const SUPPORTED: bool = true;
+
+pub(crate) type Locales = alloc::vec::IntoIter<String>;
+
+pub(crate) fn get() -> LocaleList {
+    let mut languages = navigator_languages();
+    if languages.is_empty() {
+        languages.extend(intl_locale());
+    }
+    LocaleList::new(languages.into_iter())
+}
+
+pub(crate) fn explain(report: &mut String) -> fmt::Result {
+    writeln!(report, "Provider: web-sys")?;
+    writeln!(
+        report,
+        "  navigator.languages = {:?}",
+        navigator_languages()
+    )?;
+    writeln!(
+        report,
+        "  Intl.DateTimeFormat().resolvedOptions().locale = {:?}",
+        intl_locale()
+    )
+}
+
+fn navigator_languages() -> Vec<String> {
     let languages = match global() {
         GlobalType::Window(window) => window.navigator().languages(),
         GlobalType::Worker(worker) => worker.navigator().languages(),
     };
+    reported_languages(&languages)
+}
+
+/// Normalizes the entries of `navigator.languages` like the native providers' tags, so that
+/// repeats differing only in case or separator, which some webviews and extensions add, are
+/// skipped by [`LocaleList`].
+///
+/// Entries that aren't strings or well-formed tags are dropped, as are the empty or `und`
+/// entries of privacy-hardened browsers, so that [`get`] falls back to `Intl` instead.
+fn reported_languages(languages: &Array) -> Vec<String> {
     languages
         .values()
         .into_iter()
         .flat_map(|v| v.and_then(|v| v.dyn_into::<JsString>()))
-        .map(String::from)
+        .filter_map(|language| normalize_reported(&String::from(language)))
+        .collect()
+}
+
+/// Reads the locale `Intl.DateTimeFormat` resolves to by default, as a last resort.
+///
+/// Some embedded webviews, like those of smart TVs, leave `navigator.languages` empty while
+/// `Intl` still reports the host's locale. Everything is looked up by reflection, so a runtime
+/// without `Intl` yields [`None`] instead of throwing.
+fn intl_locale() -> Option<String> {
+    let intl = property(&js_sys::global(), "Intl")?;
+    let constructor: Function = property(&intl, "DateTimeFormat")?.dyn_into().ok()?;
+    let format = Reflect::construct(&constructor, &Array::new()).ok()?;
+    let resolved_options: Function = property(&format, "resolvedOptions")?.dyn_into().ok()?;
+    let options = resolved_options.call0(&format).ok()?;
+    property(&options, "locale")?
+        .as_string()
+        .and_then(|locale| normalize_reported(&locale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::reported_languages;
+    use crate::LocaleList;
+    use alloc::vec::Vec;
+    use js_sys::Array;
+    use wasm_bindgen::JsValue;
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[test]
+    fn normalizes_and_dedups_languages() {
+        // A stand-in for `navigator.languages` as some webviews report it.
+        let languages = Array::new();
+        for language in ["en-US", "en", "en-US", "en_us", "", "de--DE"] {
+            languages.push(&JsValue::from_str(language));
+        }
+        languages.push(&JsValue::from_f64(1.0));
+
+        let locales: Vec<_> = LocaleList::new(reported_languages(&languages).into_iter()).collect();
+        assert_eq!(locales, ["en-US", "en"]);
+    }
+
+    #[test]
+    fn drops_undetermined_languages() {
+        // What browsers in some privacy modes report.
+        let languages = Array::new();
+        for language in ["", "und"] {
+            languages.push(&JsValue::from_str(language));
+        }
+        assert!(reported_languages(&languages).is_empty());
+    }
 }
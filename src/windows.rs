@@ -1,50 +1,260 @@
-use alloc::{string::String, vec::Vec};
+use alloc::{string::String, vec, vec::Vec};
+use core::{convert::TryFrom, fmt, fmt::Write};
+
+pub(crate) use crate::windows_names::Locales;
+use crate::{windows_names::decode_locale_name, windows_zones::windows_to_iana, LocaleList};
 
 #[path = "./windows_sys.rs"]
 mod windows_sys;
-use windows_sys::{GetUserPreferredUILanguages, MUI_LANGUAGE_NAME, TRUE};
+use windows_sys::{
+    GetDynamicTimeZoneInformation, GetLastError, GetLocaleInfoEx, GetModuleHandleW, GetProcAddress,
+    GetThreadPreferredUILanguages, GetUserDefaultLocaleName, GetUserPreferredUILanguages,
+    LCIDToLocaleName, LocaleNameToLCID, BOOL, DYNAMIC_TIME_ZONE_INFORMATION,
+    ERROR_INSUFFICIENT_BUFFER, LOCALE_ALLOW_NEUTRAL_NAMES, LOCALE_NAME_MAX_LENGTH,
+    LOCALE_SLOCALIZEDDISPLAYNAME, LOCALE_SNATIVEDISPLAYNAME, MUI_LANGUAGE_NAME,
+    MUI_THREAD_LANGUAGES, PWSTR, TIME_ZONE_ID_INVALID, TRUE,
+};
+
+/// The LCID Windows assigns to every locale that doesn't have a real one.
+const LOCALE_CUSTOM_UNSPECIFIED: u32 = 0x1000;
+
+/// How many times to query the languages before giving up on them changing in between.
+const MAX_ATTEMPTS: usize = 3;
 
+pub(crate) const SUPPORTED: bool = true;
+
+pub(crate) fn get() -> LocaleList {
+    LocaleList::new(Locales::new(read_languages().unwrap_or_default()))
+}
+
+/// Returns the UI languages set for the calling thread with `SetThreadPreferredUILanguages`.
+pub(crate) fn get_thread() -> Locales {
+    let buffer = read_language_list(
+        GetThreadPreferredUILanguages,
+        MUI_LANGUAGE_NAME | MUI_THREAD_LANGUAGES,
+    );
+    Locales::new(buffer.unwrap_or_default())
+}
+
+pub(crate) fn explain(report: &mut String) -> fmt::Result {
+    writeln!(report, "Provider: Windows")?;
+    match read_languages() {
+        Ok(buffer) => {
+            let names: Vec<String> = buffer
+                .split(|i| *i == 0)
+                .filter(|p| !p.is_empty())
+                .map(String::from_utf16_lossy)
+                .collect();
+            writeln!(report, "  GetUserPreferredUILanguages = {:?}", names)?;
+        }
+        Err(code) => writeln!(
+            report,
+            "  GetUserPreferredUILanguages failed with error {}",
+            code
+        )?,
+    }
+    writeln!(report, "  GetUserDefaultLocaleName = {:?}", get_format())
+}
+
+/// Reads the raw, NUL separated list of the user's preferred UI languages, or the error code
+/// reported by `GetLastError` if that fails.
+pub(crate) fn read_languages() -> Result<Vec<u16>, u32> {
+    read_language_list(GetUserPreferredUILanguages, MUI_LANGUAGE_NAME)
+}
+
+/// The signature shared by `GetUserPreferredUILanguages` and `GetThreadPreferredUILanguages`.
+type LanguageQuery = unsafe extern "system" fn(u32, *mut u32, PWSTR, *mut u32) -> BOOL;
+
+/// Reads a raw, NUL separated list of languages with `query`, or the error code reported by
+/// `GetLastError` if that fails.
+///
+/// Retrieving the list takes two calls: one for the required buffer length, and one to fill
+/// the buffer. If the user changes their languages in between, the second call can fail
+/// because the buffer has become too small, in which case the length is queried again.
 #[allow(clippy::as_conversions)]
-pub(crate) fn get() -> impl Iterator<Item = String> {
-    let mut num_languages: u32 = 0;
-    let mut buffer_length: u32 = 0;
-
-    // Calling this with null buffer will retrieve the required buffer length
-    let success = unsafe {
-        GetUserPreferredUILanguages(
-            MUI_LANGUAGE_NAME,
-            &mut num_languages,
-            core::ptr::null_mut(),
-            &mut buffer_length,
-        )
-    } == TRUE;
-    if !success {
-        return Vec::new().into_iter();
+fn read_language_list(query: LanguageQuery, flags: u32) -> Result<Vec<u16>, u32> {
+    for _ in 0..MAX_ATTEMPTS {
+        let mut num_languages: u32 = 0;
+        let mut buffer_length: u32 = 0;
+
+        // Calling this with null buffer will retrieve the required buffer length
+        let success = unsafe {
+            query(
+                flags,
+                &mut num_languages,
+                core::ptr::null_mut(),
+                &mut buffer_length,
+            )
+        } == TRUE;
+        if !success {
+            // SAFETY: This function is safe to call and has no invariants.
+            return Err(unsafe { GetLastError() });
+        }
+        // An empty list, like that of a thread without its own languages, has nothing to read.
+        if buffer_length == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut buffer = Vec::<u16>::with_capacity(buffer_length as usize);
+
+        // Now that we have an appropriate buffer, we can query the names
+        let success = unsafe {
+            query(
+                flags,
+                &mut num_languages,
+                buffer.as_mut_ptr(),
+                &mut buffer_length,
+            )
+        } == TRUE;
+
+        if success {
+            // SAFETY: Windows wrote the required length worth of UTF-16 into our buffer, which initialized it.
+            unsafe { buffer.set_len(buffer_length as usize) };
+            return Ok(buffer);
+        }
+
+        // SAFETY: This function is safe to call and has no invariants.
+        let error = unsafe { GetLastError() };
+        if error != ERROR_INSUFFICIENT_BUFFER {
+            return Err(error);
+        }
     }
 
-    let mut buffer = Vec::<u16>::with_capacity(buffer_length as usize);
+    Err(ERROR_INSUFFICIENT_BUFFER)
+}
 
-    // Now that we have an appropriate buffer, we can query the names
-    let mut result = Vec::with_capacity(num_languages as usize);
-    let success = unsafe {
-        GetUserPreferredUILanguages(
-            MUI_LANGUAGE_NAME,
-            &mut num_languages,
-            buffer.as_mut_ptr(),
-            &mut buffer_length,
+/// Returns the user's regional format locale, which is independent of the display languages.
+#[allow(clippy::as_conversions)]
+pub(crate) fn get_format() -> Option<String> {
+    let mut buffer = [0u16; LOCALE_NAME_MAX_LENGTH as usize];
+
+    // SAFETY: `buffer` is writable and its length is passed along with it.
+    let len = unsafe { GetUserDefaultLocaleName(buffer.as_mut_ptr(), buffer.len() as i32) };
+
+    decode_locale_name(&buffer, len)
+}
+
+/// The signature of `GetUserDefaultGeoName`.
+type GetUserDefaultGeoNameFn = unsafe extern "system" fn(PWSTR, i32) -> i32;
+
+/// Returns the name of the user's home location, such as `US`.
+///
+/// `GetUserDefaultGeoName` only exists since Windows 10 version 1709, so it's looked up at
+/// runtime instead of being linked, which would prevent the program from starting on older
+/// versions.
+#[allow(clippy::as_conversions)]
+pub(crate) fn get_geo_name() -> Option<String> {
+    let kernel32: Vec<u16> = "kernel32.dll"
+        .encode_utf16()
+        .chain(core::iter::once(0))
+        .collect();
+
+    // SAFETY: `kernel32` is a valid, NUL terminated UTF-16 string.
+    let module = unsafe { GetModuleHandleW(kernel32.as_ptr()) };
+    if module == 0 {
+        return None;
+    }
+    // SAFETY: `module` is a loaded module, and the name is NUL terminated.
+    let function = unsafe { GetProcAddress(module, b"GetUserDefaultGeoName\0".as_ptr()) }?;
+    // SAFETY: The function exported under this name has this signature.
+    let get_user_default_geo_name = unsafe {
+        core::mem::transmute::<unsafe extern "system" fn() -> isize, GetUserDefaultGeoNameFn>(
+            function,
         )
-    } == TRUE;
-
-    if success {
-        // SAFETY: Windows wrote the required length worth of UTF-16 into our buffer, which initialized it.
-        unsafe { buffer.set_len(buffer_length as usize) };
-        // The buffer contains names split by null char (0), and ends with two null chars (00)
-        for part in buffer.split(|i| *i == 0).filter(|p| !p.is_empty()) {
-            if let Ok(locale) = String::from_utf16(part) {
-                result.push(locale);
-            }
+    };
+
+    let mut buffer = [0u16; LOCALE_NAME_MAX_LENGTH as usize];
+
+    // SAFETY: `buffer` is writable and its length is passed along with it.
+    let len = unsafe { get_user_default_geo_name(buffer.as_mut_ptr(), buffer.len() as i32) };
+
+    decode_locale_name(&buffer, len)
+}
+
+/// Returns the IANA id of the current time zone, mapped from its Windows key name.
+pub(crate) fn get_timezone() -> Option<String> {
+    // SAFETY: The structure only contains integers and arrays of them, for which all zeroes
+    // is a valid value.
+    let mut info: DYNAMIC_TIME_ZONE_INFORMATION = unsafe { core::mem::zeroed() };
+
+    // SAFETY: `info` is valid to write to.
+    if unsafe { GetDynamicTimeZoneInformation(&mut info) } == TIME_ZONE_ID_INVALID {
+        return None;
+    }
+
+    let key_name = &info.TimeZoneKeyName;
+    let len = key_name
+        .iter()
+        .position(|c| *c == 0)
+        .unwrap_or(key_name.len());
+    let key_name = String::from_utf16(&key_name[..len]).ok()?;
+
+    windows_to_iana(&key_name).map(String::from)
+}
+
+/// Returns the name of the locale `tag`, either in the language of the Windows UI, or in its
+/// own language when `in_locale` is `tag` itself.
+///
+/// `GetLocaleInfoEx` can't name a locale in any other language, so that returns [`None`].
+pub(crate) fn display_name(tag: &str, in_locale: Option<&str>) -> Option<String> {
+    match in_locale {
+        None => locale_info(tag, LOCALE_SLOCALIZEDDISPLAYNAME),
+        Some(in_locale) if in_locale.eq_ignore_ascii_case(tag) => {
+            locale_info(tag, LOCALE_SNATIVEDISPLAYNAME)
         }
+        Some(_) => None,
     }
+}
+
+/// Reads the string `info` of the locale `name` with `GetLocaleInfoEx`.
+#[allow(clippy::as_conversions)]
+fn locale_info(name: &str, info: u32) -> Option<String> {
+    let name: Vec<u16> = name.encode_utf16().chain(core::iter::once(0)).collect();
+
+    // SAFETY: `name` is a valid, NUL terminated UTF-16 string. Passing no buffer queries the
+    // length it needs, including the NUL terminator.
+    let len = unsafe { GetLocaleInfoEx(name.as_ptr(), info, core::ptr::null_mut(), 0) };
+    let mut buffer = vec![0u16; usize::try_from(len).ok().filter(|len| *len > 0)?];
+
+    // SAFETY: `buffer` is writable and its length is passed along with it.
+    let len = unsafe {
+        GetLocaleInfoEx(
+            name.as_ptr(),
+            info,
+            buffer.as_mut_ptr(),
+            buffer.len() as i32,
+        )
+    };
+
+    decode_locale_name(&buffer, len)
+}
+
+/// Converts a locale name into its legacy numeric LCID.
+pub(crate) fn locale_name_to_lcid(name: &str) -> Option<u32> {
+    let name: Vec<u16> = name.encode_utf16().chain(core::iter::once(0)).collect();
+
+    // SAFETY: `name` is a valid, NUL terminated UTF-16 string.
+    let lcid = unsafe { LocaleNameToLCID(name.as_ptr(), LOCALE_ALLOW_NEUTRAL_NAMES) };
+
+    // Locales added after LCIDs were deprecated all share the same placeholder value, which
+    // can't be converted back and so isn't useful to callers.
+    Some(lcid).filter(|lcid| *lcid != 0 && *lcid != LOCALE_CUSTOM_UNSPECIFIED)
+}
+
+/// Converts a legacy numeric LCID into its locale name.
+#[allow(clippy::as_conversions)]
+pub(crate) fn lcid_to_locale_name(lcid: u32) -> Option<String> {
+    let mut buffer = [0u16; LOCALE_NAME_MAX_LENGTH as usize];
+
+    // SAFETY: `buffer` is writable and its length is passed along with it.
+    let len = unsafe {
+        LCIDToLocaleName(
+            lcid,
+            buffer.as_mut_ptr(),
+            buffer.len() as i32,
+            LOCALE_ALLOW_NEUTRAL_NAMES,
+        )
+    };
 
-    result.into_iter()
+    decode_locale_name(&buffer, len)
 }
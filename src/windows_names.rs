@@ -0,0 +1,124 @@
+use alloc::{string::String, vec::Vec};
+use core::convert::TryFrom;
+
+/// The names in the NUL separated list returned by `GetUserPreferredUILanguages`, decoded one
+/// at a time.
+///
+/// The list ends with two NULs, and some systems also put empty names between entries. Empty
+/// names are skipped without ending the iteration.
+///
+/// Only the raw buffer is kept, so taking the first few entries doesn't decode or allocate the
+/// rest of the list.
+pub(crate) struct Locales {
+    buffer: Vec<u16>,
+    /// The index in `buffer` of the next name to decode.
+    offset: usize,
+}
+
+impl Locales {
+    pub(crate) fn new(buffer: Vec<u16>) -> Self {
+        Self { buffer, offset: 0 }
+    }
+}
+
+impl Iterator for Locales {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(rest) = self.buffer.get(self.offset..).filter(|r| !r.is_empty()) {
+            let len = rest.iter().position(|c| *c == 0).unwrap_or(rest.len());
+            self.offset += len + 1;
+            // Besides the final one, empty names have been seen between entries, so only the
+            // end of the buffer ends the list.
+            if len == 0 {
+                continue;
+            }
+            if let Some(name) = decode_name(&rest[..len]) {
+                return Some(name);
+            }
+        }
+        None
+    }
+}
+
+/// Decodes a single name from the list returned by `GetUserPreferredUILanguages`.
+///
+/// A byte order mark at the start of a name is removed. Some configurations append other data
+/// to the list, so names that aren't valid UTF-16 or [well-formed](crate::is_valid_bcp47)
+/// language tags are dropped instead of being passed on verbatim.
+fn decode_name(name: &[u16]) -> Option<String> {
+    let name = String::from_utf16(name).ok()?;
+    let name = match name.strip_prefix('\u{feff}') {
+        Some(name) => String::from(name),
+        None => name,
+    };
+    Some(name).filter(|name| crate::is_valid_bcp47(name))
+}
+
+/// Decodes a locale name, or another string about a locale, written by a Win32 API that returns
+/// the length it wrote, including the NUL terminator, or `0` on failure.
+pub(crate) fn decode_locale_name(buffer: &[u16], len: i32) -> Option<String> {
+    let len = usize::try_from(len).ok().filter(|len| *len > 1)?;
+    String::from_utf16(buffer.get(..len - 1)?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Locales;
+    use alloc::{string::String, vec::Vec};
+
+    fn decode_languages(buffer: &[u16]) -> Vec<String> {
+        Locales::new(buffer.to_vec()).collect()
+    }
+
+    fn buffer(names: &str) -> Vec<u16> {
+        names.encode_utf16().collect()
+    }
+
+    #[test]
+    fn decodes_languages() {
+        assert_eq!(
+            decode_languages(&buffer("en-US\0fr-FR\0\0")),
+            ["en-US", "fr-FR"]
+        );
+        assert_eq!(decode_languages(&buffer("\u{feff}en-US\0\0")), ["en-US"]);
+        assert_eq!(
+            decode_languages(&buffer("en-US\0\0de-DE\0\0")),
+            ["en-US", "de-DE"]
+        );
+        assert_eq!(
+            decode_languages(&buffer("en-US\0\0fr-FR\0\0")),
+            ["en-US", "fr-FR"]
+        );
+        assert_eq!(
+            decode_languages(&buffer("\0\0en-US\0\0\0fr-FR\0\0")),
+            ["en-US", "fr-FR"]
+        );
+        assert_eq!(
+            decode_languages(&buffer("en\u{1}US\0\u{feff}\0fr\0\0")),
+            ["fr"]
+        );
+        assert!(decode_languages(&[0xD800, 0, 0]).is_empty());
+        assert!(decode_languages(&[]).is_empty());
+        assert_eq!(decode_languages(&buffer("en-US")), ["en-US"]);
+    }
+
+    #[test]
+    fn drops_malformed_names() {
+        assert_eq!(decode_languages(&buffer("en-US\0\u{1}bad\0\0")), ["en-US"]);
+        assert_eq!(
+            decode_languages(&buffer("en-US-x-corp\0en_US\0de--DE\0qps-ploc\0\0")),
+            ["en-US-x-corp", "qps-ploc"]
+        );
+    }
+
+    #[test]
+    fn decodes_lazily() {
+        let mut locales = Locales::new(buffer("en-US\0fr-FR\0\0"));
+        assert_eq!(locales.next().as_deref(), Some("en-US"));
+        assert_eq!(locales.offset, "en-US\0".len());
+        assert_eq!(locales.next().as_deref(), Some("fr-FR"));
+        assert_eq!(locales.next(), None);
+        assert_eq!(locales.next(), None);
+    }
+}
@@ -8,6 +8,42 @@
     clippy::all
 )]
 #[link(name = "kernel32")]
+extern "system" {
+    pub fn GetDynamicTimeZoneInformation(
+        ptimezoneinformation: *mut DYNAMIC_TIME_ZONE_INFORMATION,
+    ) -> u32;
+}
+#[link(name = "kernel32")]
+extern "system" {
+    pub fn GetLastError() -> WIN32_ERROR;
+}
+#[link(name = "kernel32")]
+extern "system" {
+    pub fn GetLocaleInfoEx(lplocalename: PCWSTR, lctype: u32, lplcdata: PWSTR, cchdata: i32)
+        -> i32;
+}
+#[link(name = "kernel32")]
+extern "system" {
+    pub fn GetModuleHandleW(lpmodulename: PCWSTR) -> HMODULE;
+}
+#[link(name = "kernel32")]
+extern "system" {
+    pub fn GetProcAddress(hmodule: HMODULE, lpprocname: PCSTR) -> FARPROC;
+}
+#[link(name = "kernel32")]
+extern "system" {
+    pub fn GetThreadPreferredUILanguages(
+        dwflags: u32,
+        pulnumlanguages: *mut u32,
+        pwszlanguagesbuffer: PWSTR,
+        pcchlanguagesbuffer: *mut u32,
+    ) -> BOOL;
+}
+#[link(name = "kernel32")]
+extern "system" {
+    pub fn GetUserDefaultLocaleName(lplocalename: PWSTR, cchlocalename: i32) -> i32;
+}
+#[link(name = "kernel32")]
 extern "system" {
     pub fn GetUserPreferredUILanguages(
         dwflags: u32,
@@ -16,7 +52,63 @@ extern "system" {
         pcchlanguagesbuffer: *mut u32,
     ) -> BOOL;
 }
+#[link(name = "kernel32")]
+extern "system" {
+    pub fn LCIDToLocaleName(locale: u32, lpname: PWSTR, cchname: i32, dwflags: u32) -> i32;
+}
+#[link(name = "kernel32")]
+extern "system" {
+    pub fn LocaleNameToLCID(lpname: PCWSTR, dwflags: u32) -> u32;
+}
 pub type BOOL = i32;
+pub type BOOLEAN = u8;
+#[repr(C)]
+pub struct DYNAMIC_TIME_ZONE_INFORMATION {
+    pub Bias: i32,
+    pub StandardName: [u16; 32],
+    pub StandardDate: SYSTEMTIME,
+    pub StandardBias: i32,
+    pub DaylightName: [u16; 32],
+    pub DaylightDate: SYSTEMTIME,
+    pub DaylightBias: i32,
+    pub TimeZoneKeyName: [u16; 128],
+    pub DynamicDaylightTimeDisabled: BOOLEAN,
+}
+impl ::core::marker::Copy for DYNAMIC_TIME_ZONE_INFORMATION {}
+impl ::core::clone::Clone for DYNAMIC_TIME_ZONE_INFORMATION {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+pub const ERROR_INSUFFICIENT_BUFFER: WIN32_ERROR = 122u32;
+pub type FARPROC = ::core::option::Option<unsafe extern "system" fn() -> isize>;
+pub type HMODULE = isize;
+pub const LOCALE_ALLOW_NEUTRAL_NAMES: u32 = 134217728u32;
+pub const LOCALE_NAME_MAX_LENGTH: u32 = 85u32;
+pub const LOCALE_SLOCALIZEDDISPLAYNAME: u32 = 2u32;
+pub const LOCALE_SNATIVEDISPLAYNAME: u32 = 115u32;
 pub const MUI_LANGUAGE_NAME: u32 = 8u32;
+pub const MUI_THREAD_LANGUAGES: u32 = 64u32;
+pub type PCSTR = *const u8;
+pub type PCWSTR = *const u16;
 pub type PWSTR = *mut u16;
+#[repr(C)]
+pub struct SYSTEMTIME {
+    pub wYear: u16,
+    pub wMonth: u16,
+    pub wDayOfWeek: u16,
+    pub wDay: u16,
+    pub wHour: u16,
+    pub wMinute: u16,
+    pub wSecond: u16,
+    pub wMilliseconds: u16,
+}
+impl ::core::marker::Copy for SYSTEMTIME {}
+impl ::core::clone::Clone for SYSTEMTIME {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+pub const TIME_ZONE_ID_INVALID: u32 = 4294967295u32;
 pub const TRUE: BOOL = 1i32;
+pub type WIN32_ERROR = u32;
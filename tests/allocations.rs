@@ -0,0 +1,72 @@
+//! Checks that resolving the locale from the environment on Unix doesn't regress in how much it
+//! allocates.
+#![cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+/// Counts the allocations made while `COUNTING` is set.
+struct CountingAllocator;
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.load(Ordering::SeqCst) {
+            ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if COUNTING.load(Ordering::SeqCst) {
+            ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// How many allocations resolving `LANG=en_US.UTF-8` may make: reading the variable, removing
+/// its quotes, converting it to BCP 47, and keeping track of it to skip repeated entries.
+///
+/// Lower this when an optimization lands, so that it can't regress silently.
+const MAX_ALLOCATIONS: usize = 7;
+
+/// Returns how many allocations `f` makes.
+fn count_allocations<T>(f: impl FnOnce() -> T) -> usize {
+    ALLOCATIONS.store(0, Ordering::SeqCst);
+    COUNTING.store(true, Ordering::SeqCst);
+    let result = f();
+    COUNTING.store(false, Ordering::SeqCst);
+    drop(result);
+    ALLOCATIONS.load(Ordering::SeqCst)
+}
+
+// This is the only test in this binary, so that nothing else allocates or reads the environment
+// while it runs.
+#[test]
+fn resolving_lang_allocates_little() {
+    for variable in ["LANGUAGE", "LC_ALL", "LC_MESSAGES"] {
+        env::remove_var(variable);
+    }
+    env::set_var("LANG", "en_US.UTF-8");
+
+    let allocations = count_allocations(|| sys_locale::get_locales().next());
+    assert!(
+        allocations <= MAX_ALLOCATIONS,
+        "resolving LANG made {} allocations, more than {}",
+        allocations,
+        MAX_ALLOCATIONS
+    );
+}
@@ -0,0 +1,29 @@
+#![cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+
+use std::{collections::HashMap, env};
+
+use sys_locale::{get_locales, LocaleList};
+
+#[test]
+fn matches_the_process_environment() {
+    let vars: HashMap<String, String> = env::vars_os()
+        .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+        .collect();
+    let from_env: Vec<String> = LocaleList::from_env(&vars).collect();
+
+    // The configuration file fallbacks only apply when the environment names no locale.
+    if !from_env.is_empty() {
+        assert_eq!(from_env, get_locales().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn resolves_a_captured_environment() {
+    let mut vars = HashMap::new();
+    vars.insert(String::from("LC_ALL"), String::from(""));
+    vars.insert(String::from("LC_MESSAGES"), String::from("\"de_DE.UTF-8\""));
+    vars.insert(String::from("LANG"), String::from("de_DE.UTF-8"));
+    assert_eq!(LocaleList::from_env(&vars).collect::<Vec<_>>(), ["de-DE"]);
+
+    assert_eq!(LocaleList::from_env(&HashMap::new()).next(), None);
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "mock")]
+
+use sys_locale::{
+    clear_mock_locales, get_format_locale, get_locale, get_locale_canonical, get_locale_result,
+    get_locales, negotiate, set_mock_locales, LocaleError,
+};
+
+// The mock is global, so everything is checked from a single test to keep it deterministic.
+#[test]
+fn mocked_locales_replace_the_system() {
+    set_mock_locales(vec![
+        String::from("fr-CA"),
+        String::from("en-US"),
+        String::from("fr-CA"),
+    ]);
+    assert_eq!(get_locale().as_deref(), Some("fr-CA"));
+    assert_eq!(get_locales().collect::<Vec<_>>(), ["fr-CA", "en-US"]);
+    assert_eq!(get_format_locale().as_deref(), Some("fr-CA"));
+    assert_eq!(negotiate(&["de", "en", "fr"]), Some("fr"));
+
+    set_mock_locales(vec![String::from("iw-il"), String::from("en-US")]);
+    assert_eq!(get_locale_canonical().as_deref(), Some("he-IL"));
+    let expected = if cfg!(feature = "canonical") {
+        "he-IL"
+    } else {
+        "iw-il"
+    };
+    assert_eq!(get_locale().as_deref(), Some(expected));
+
+    set_mock_locales(Vec::new());
+    assert_eq!(get_locale(), None);
+    assert_eq!(get_format_locale(), None);
+    assert_eq!(get_locale_result(), Err(LocaleError::NotFound));
+
+    clear_mock_locales();
+    // The `canonical` feature only applies to `get_locale`, not to the list.
+    let mut first = get_locales().next();
+    if cfg!(feature = "canonical") {
+        first = first.map(|tag| sys_locale::canonicalize(&tag).unwrap_or(tag));
+    }
+    assert_eq!(get_locale(), first);
+}
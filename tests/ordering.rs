@@ -0,0 +1,107 @@
+//! Checks that every list derived from the preferred locales keeps the most preferred locale
+//! first and doesn't reorder entries.
+#![cfg(not(all(target_family = "wasm", not(unix))))]
+
+use sys_locale::{get_all_locales, get_locale, get_locales, get_locales_expanded, locales};
+
+/// Returns `true` if every entry of `subset` appears in `list` in the same relative order.
+fn is_ordered_subset(subset: &[String], list: &[String]) -> bool {
+    let mut remaining = list.iter();
+    subset.iter().all(|entry| remaining.any(|e| e == entry))
+}
+
+/// Returns the first entry of `preferred` as [`get_locale`] returns it, which is canonicalized
+/// with the `canonical` feature.
+fn first_as_get_locale(preferred: &[String]) -> Option<String> {
+    let first = preferred.first().cloned();
+    if cfg!(feature = "canonical") {
+        first.map(|tag| sys_locale::canonicalize(&tag).unwrap_or(tag))
+    } else {
+        first
+    }
+}
+
+fn check_ordering_contract() -> Vec<String> {
+    let preferred: Vec<String> = get_locales().collect();
+    assert_eq!(get_locale(), first_as_get_locale(&preferred));
+
+    // Entries are never repeated
+    for (i, locale) in preferred.iter().enumerate() {
+        assert!(
+            !preferred[..i].contains(locale),
+            "{} was returned twice",
+            locale
+        );
+    }
+
+    sys_locale::reset_locales();
+    assert_eq!(locales(), preferred.as_slice());
+
+    let all: Vec<String> = get_all_locales().collect();
+    assert!(all.starts_with(&preferred));
+
+    let expanded: Vec<String> = get_locales_expanded().collect();
+    let preferred_fallbacks: Vec<String> = preferred
+        .iter()
+        .filter(|locale| expanded.contains(locale))
+        .cloned()
+        .collect();
+    assert!(is_ordered_subset(&preferred_fallbacks, &expanded));
+    assert_eq!(expanded.first(), preferred_fallbacks.first());
+
+    preferred
+}
+
+// Everything runs in one test, since the mocked list is global and would otherwise race with the
+// checks against the system.
+#[test]
+fn preferred_locale_is_first() {
+    check_ordering_contract();
+
+    #[cfg(feature = "mock")]
+    {
+        sys_locale::set_mock_locales(
+            ["fr-CA", "en-US", "fr-CA", "de-DE"]
+                .iter()
+                .map(|l| String::from(*l))
+                .collect(),
+        );
+        assert_eq!(check_ordering_contract(), ["fr-CA", "en-US", "de-DE"]);
+        assert_eq!(
+            get_locales_expanded().collect::<Vec<_>>(),
+            ["fr-CA", "fr", "en-US", "en", "de-DE", "de"]
+        );
+
+        sys_locale::set_mock_locales(vec![String::from("en-US")]);
+        assert_eq!(check_ordering_contract(), ["en-US"]);
+
+        sys_locale::clear_mock_locales();
+        sys_locale::reset_locales();
+    }
+}
+
+// The Unix provider's own ordering is checked on a captured environment, since changing the
+// environment of the process races with other threads reading it.
+#[cfg(all(unix, not(any(target_vendor = "apple", target_os = "android"))))]
+#[test]
+fn unix_variables_keep_their_order() {
+    use std::collections::HashMap;
+    use sys_locale::LocaleList;
+
+    let mut vars = HashMap::new();
+    vars.insert(
+        String::from("LANGUAGE"),
+        String::from("fr_CA:en_US.UTF-8:fr_CA:C"),
+    );
+    vars.insert(String::from("LC_ALL"), String::from(""));
+    vars.insert(String::from("LC_MESSAGES"), String::from("de_DE.UTF-8"));
+    vars.insert(String::from("LANG"), String::from("en_US.UTF-8"));
+    assert_eq!(
+        LocaleList::from_env(&vars).collect::<Vec<_>>(),
+        ["fr-CA", "en-US", "de-DE"]
+    );
+
+    vars.remove("LANGUAGE");
+    vars.remove("LC_MESSAGES");
+    assert_eq!(LocaleList::from_env(&vars).collect::<Vec<_>>(), ["en-US"]);
+}
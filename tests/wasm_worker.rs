@@ -1,4 +1,8 @@
-#![cfg(all(target_family = "wasm", feature = "js", not(unix)))]
+#![cfg(all(
+    target_family = "wasm",
+    any(feature = "js", feature = "js-minimal"),
+    not(unix)
+))]
 
 use wasm_bindgen_test::wasm_bindgen_test as test;
 wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_worker);